@@ -0,0 +1,121 @@
+//! Special abilities a part can have on top of its stats, declared per part in the
+//! part catalogue. Like the stats they're hidden until the player has seen them at
+//! work, after which the builder lists them.
+
+use serde::Deserialize;
+
+pub trait Ability {
+    /// stable, the journal refers to abilities by id
+    fn get_id(&self) -> &'static str;
+    fn get_name(&self) -> &'static str;
+    fn get_description(&self) -> &'static str;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum HeadAbility {
+    /// sees humans hidden behind trees
+    XRay,
+}
+
+impl Ability for HeadAbility {
+    fn get_id(&self) -> &'static str {
+        match self {
+            HeadAbility::XRay => "x-ray",
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        match self {
+            HeadAbility::XRay => "X-ray",
+        }
+    }
+
+    fn get_description(&self) -> &'static str {
+        match self {
+            HeadAbility::XRay => "Spots humans hiding behind trees.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum BodyAbility {
+    /// takes this much less damage from every shot
+    Armour(f32),
+}
+
+impl Ability for BodyAbility {
+    fn get_id(&self) -> &'static str {
+        match self {
+            BodyAbility::Armour(_) => "armour",
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        match self {
+            BodyAbility::Armour(_) => "Armour",
+        }
+    }
+
+    fn get_description(&self) -> &'static str {
+        match self {
+            BodyAbility::Armour(_) => "Takes less damage from every shot.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ArmsAbility {
+    /// a hit in melee also hits every other human this close to the target
+    Cleave(f32),
+    /// a hit in melee shoves the human this far back
+    Knockback(f32),
+}
+
+impl Ability for ArmsAbility {
+    fn get_id(&self) -> &'static str {
+        match self {
+            ArmsAbility::Cleave(_) => "cleave",
+            ArmsAbility::Knockback(_) => "knockback",
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        match self {
+            ArmsAbility::Cleave(_) => "Cleave",
+            ArmsAbility::Knockback(_) => "Knockback",
+        }
+    }
+
+    fn get_description(&self) -> &'static str {
+        match self {
+            ArmsAbility::Cleave(_) => "Hits the humans standing next to its target too.",
+            ArmsAbility::Knockback(_) => "Shoves whoever it hits back.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum LegsAbility {
+    /// leaps straight over trees rather than walking round them
+    Jump,
+}
+
+impl Ability for LegsAbility {
+    fn get_id(&self) -> &'static str {
+        match self {
+            LegsAbility::Jump => "jump",
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        match self {
+            LegsAbility::Jump => "Jump",
+        }
+    }
+
+    fn get_description(&self) -> &'static str {
+        match self {
+            LegsAbility::Jump => "Leaps over trees instead of going round.",
+        }
+    }
+}
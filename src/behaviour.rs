@@ -0,0 +1,260 @@
+//! How a monster fends for itself at night when the player hasn't given it an order.
+//! Each profile looks at what the monster can tell about its surroundings and picks
+//! what to do this tick; the simulation then carries it out.
+
+/// A human the monster knows about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sighting {
+    pub human: usize, // index
+    pub distance: f32, // from the monster
+    pub nest_distance: f32, // from the nest
+}
+
+/// What a monster can tell about its situation this tick.
+#[derive(Debug, Clone, Copy)]
+pub struct Situation {
+    pub hp_fraction: f32, // 1 at full health
+    pub target: Option<Sighting>, // the closest human the monster can see
+    pub closest_human: Option<Sighting>, // the closest anywhere, seen or not
+    pub attack_range: f32,
+    pub ready_to_attack: bool, // whether its attack has cooled down
+}
+
+impl Situation {
+    /// the human in sight, if it's close enough to attack
+    fn target_in_range(&self) -> Option<usize> {
+        self.target
+            .filter(|target| target.distance <= self.attack_range)
+            .map(|target| target.human)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intent {
+    /// hit or spit at a human in range
+    Attack(usize),
+    /// walk towards a human
+    Chase(usize),
+    /// back away from a human while still facing them
+    Retreat(usize),
+    /// go back to its spot by the nest and keep watch
+    Guard,
+}
+
+pub trait Behaviour {
+    /// stable, saves refer to behaviours by id
+    fn get_id(&self) -> &'static str;
+    fn get_name(&self) -> &'static str;
+    fn get_description(&self) -> &'static str;
+    fn decide(&self, situation: &Situation) -> Intent;
+}
+
+/// Fights what it sees and guards the nest otherwise.
+pub struct Steady;
+
+impl Behaviour for Steady {
+    fn get_id(&self) -> &'static str {
+        "steady"
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Steady"
+    }
+
+    fn get_description(&self) -> &'static str {
+        "Goes for any human it sees, otherwise guards the nest."
+    }
+
+    fn decide(&self, situation: &Situation) -> Intent {
+        if let Some(human) = situation.target_in_range() {
+            Intent::Attack(human)
+        } else if let Some(target) = situation.target {
+            Intent::Chase(target.human)
+        } else {
+            Intent::Guard
+        }
+    }
+}
+
+/// Goes hunting for humans across the map, even ones it can't see.
+pub struct Aggressive;
+
+impl Behaviour for Aggressive {
+    fn get_id(&self) -> &'static str {
+        "aggressive"
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Aggressive"
+    }
+
+    fn get_description(&self) -> &'static str {
+        "Hunts down the nearest human, wherever they are."
+    }
+
+    fn decide(&self, situation: &Situation) -> Intent {
+        if let Some(human) = situation.target_in_range() {
+            Intent::Attack(human)
+        } else if let Some(target) = situation.target.or(situation.closest_human) {
+            Intent::Chase(target.human)
+        } else {
+            Intent::Guard
+        }
+    }
+}
+
+/// how far from the nest a defensive monster will go after a human
+pub const DEFENSIVE_LEASH: f32 = 220.0;
+
+/// Sticks by the nest, only going after humans that come close to it.
+pub struct Defensive;
+
+impl Behaviour for Defensive {
+    fn get_id(&self) -> &'static str {
+        "defensive"
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Defensive"
+    }
+
+    fn get_description(&self) -> &'static str {
+        "Stays by the nest, only chasing humans that get close to it."
+    }
+
+    fn decide(&self, situation: &Situation) -> Intent {
+        if let Some(human) = situation.target_in_range() {
+            Intent::Attack(human)
+        } else {
+            match situation.target {
+                Some(target) if target.nest_distance <= DEFENSIVE_LEASH => Intent::Chase(target.human),
+                _ => Intent::Guard,
+            }
+        }
+    }
+}
+
+/// below this share of its health a skirmisher stops fighting and heads home
+pub const SKIRMISHER_RETREAT_HP: f32 = 0.35;
+
+/// Hits and runs, backing off while its attack cools down.
+pub struct Skirmisher;
+
+impl Behaviour for Skirmisher {
+    fn get_id(&self) -> &'static str {
+        "skirmisher"
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Skirmisher"
+    }
+
+    fn get_description(&self) -> &'static str {
+        "Darts in to attack and backs off to recover, running home when badly hurt."
+    }
+
+    fn decide(&self, situation: &Situation) -> Intent {
+        let target = match situation.target {
+            Some(target) => target,
+            None => return Intent::Guard,
+        };
+        if situation.hp_fraction < SKIRMISHER_RETREAT_HP {
+            Intent::Guard
+        } else if !situation.ready_to_attack {
+            Intent::Retreat(target.human)
+        } else if target.distance <= situation.attack_range {
+            Intent::Attack(target.human)
+        } else {
+            Intent::Chase(target.human)
+        }
+    }
+}
+
+/// Every profile a monster can be built with, the first being the default.
+pub const BEHAVIOURS: [&dyn Behaviour; 4] = [&Steady, &Aggressive, &Defensive, &Skirmisher];
+
+pub fn default_behaviour() -> &'static dyn Behaviour {
+    BEHAVIOURS[0]
+}
+
+pub fn find_behaviour(id: &str) -> Option<&'static dyn Behaviour> {
+    BEHAVIOURS.iter().copied().find(|behaviour| behaviour.get_id() == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sighting(human: usize, distance: f32, nest_distance: f32) -> Sighting {
+        Sighting {
+            human,
+            distance,
+            nest_distance,
+        }
+    }
+
+    fn situation(target: Option<Sighting>) -> Situation {
+        Situation {
+            hp_fraction: 1.0,
+            target,
+            closest_human: target,
+            attack_range: 50.0,
+            ready_to_attack: true,
+        }
+    }
+
+    #[test]
+    fn steady_attacks_in_range_chases_in_sight_and_guards_otherwise() {
+        assert_eq!(Steady.decide(&situation(Some(sighting(2, 40.0, 500.0)))), Intent::Attack(2));
+        assert_eq!(Steady.decide(&situation(Some(sighting(2, 90.0, 500.0)))), Intent::Chase(2));
+        let unseen = Situation {
+            target: None,
+            ..situation(Some(sighting(1, 300.0, 300.0)))
+        };
+        assert_eq!(Steady.decide(&unseen), Intent::Guard);
+    }
+
+    #[test]
+    fn aggressive_hunts_humans_out_of_sight() {
+        let unseen = Situation {
+            target: None,
+            ..situation(Some(sighting(1, 300.0, 300.0)))
+        };
+        assert_eq!(Aggressive.decide(&unseen), Intent::Chase(1));
+        assert_eq!(Aggressive.decide(&situation(None)), Intent::Guard);
+    }
+
+    #[test]
+    fn defensive_only_leaves_the_nest_for_close_humans() {
+        let near_nest = situation(Some(sighting(0, 100.0, DEFENSIVE_LEASH - 10.0)));
+        assert_eq!(Defensive.decide(&near_nest), Intent::Chase(0));
+        let far_off = situation(Some(sighting(0, 100.0, DEFENSIVE_LEASH + 10.0)));
+        assert_eq!(Defensive.decide(&far_off), Intent::Guard);
+        let in_range = situation(Some(sighting(0, 20.0, DEFENSIVE_LEASH + 10.0)));
+        assert_eq!(Defensive.decide(&in_range), Intent::Attack(0));
+    }
+
+    #[test]
+    fn skirmisher_backs_off_while_reloading_and_runs_home_when_hurt() {
+        let reloading = Situation {
+            ready_to_attack: false,
+            ..situation(Some(sighting(3, 20.0, 500.0)))
+        };
+        assert_eq!(Skirmisher.decide(&reloading), Intent::Retreat(3));
+        assert_eq!(Skirmisher.decide(&situation(Some(sighting(3, 20.0, 500.0)))), Intent::Attack(3));
+        let hurt = Situation {
+            hp_fraction: SKIRMISHER_RETREAT_HP / 2.0,
+            ..situation(Some(sighting(3, 20.0, 500.0)))
+        };
+        assert_eq!(Skirmisher.decide(&hurt), Intent::Guard);
+    }
+
+    #[test]
+    fn behaviours_are_found_by_id() {
+        for behaviour in BEHAVIOURS.iter() {
+            assert_eq!(find_behaviour(behaviour.get_id()).unwrap().get_name(), behaviour.get_name());
+        }
+        assert_eq!(default_behaviour().get_id(), "steady");
+        assert!(find_behaviour("cowardly").is_none());
+    }
+}
@@ -0,0 +1,177 @@
+//! Working out what a single hit does, the one place damage is decided for monsters,
+//! humans and the nest alike.
+
+use rand::Rng;
+use serde::Deserialize;
+
+/// how many times the damage a critical hit does
+pub const CRIT_MULTIPLIER: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum DamageType {
+    #[default]
+    Ballistic,
+    Slashing,
+    Blunt,
+    Fire,
+}
+
+/// The share of each type of damage shrugged off, 1 being immune. Below zero is a
+/// weakness, taking extra damage.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct Resistances {
+    pub ballistic: f32,
+    pub slashing: f32,
+    pub blunt: f32,
+    pub fire: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Ballistic => self.ballistic,
+            DamageType::Slashing => self.slashing,
+            DamageType::Blunt => self.blunt,
+            DamageType::Fire => self.fire,
+        }
+    }
+
+    /// every resistance with its name, for checking them when loading
+    pub fn get_all(&self) -> [(&'static str, f32); 4] {
+        [
+            ("ballistic", self.ballistic),
+            ("slashing", self.slashing),
+            ("blunt", self.blunt),
+            ("fire", self.fire),
+        ]
+    }
+}
+
+/// Everything about a blow or a shot that decides how much it hurts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attack {
+    pub damage: f32,
+    pub damage_type: DamageType,
+    /// from 0 to 1
+    pub crit_chance: f32,
+}
+
+/// Everything about whoever's being hit that decides how much it hurts.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Defence {
+    /// taken off every hit after resistances
+    pub armour: f32,
+    pub resistances: Resistances,
+    /// the chance, from 0 to 1, of a hit missing altogether
+    pub evasion: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitOutcome {
+    Miss,
+    Hit,
+    Critical,
+}
+
+/// What came of a hit, for the simulation to apply and the sound and HUD to show.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitResult {
+    pub outcome: HitOutcome,
+    /// what the target actually loses, 0 for a miss
+    pub damage: f32,
+    pub damage_type: DamageType,
+}
+
+impl HitResult {
+    pub fn is_miss(&self) -> bool {
+        self.outcome == HitOutcome::Miss
+    }
+}
+
+pub fn resolve_hit<R: Rng>(attack: &Attack, defence: &Defence, rng: &mut R) -> HitResult {
+    let miss = HitResult {
+        outcome: HitOutcome::Miss,
+        damage: 0.0,
+        damage_type: attack.damage_type,
+    };
+    if defence.evasion > 0.0 && rng.gen::<f32>() < defence.evasion {
+        return miss;
+    }
+    let critical = attack.crit_chance > 0.0 && rng.gen::<f32>() < attack.crit_chance;
+    let damage = if critical {
+        attack.damage * CRIT_MULTIPLIER
+    } else {
+        attack.damage
+    };
+    let damage = damage * (1.0 - defence.resistances.get(attack.damage_type));
+    HitResult {
+        outcome: if critical { HitOutcome::Critical } else { HitOutcome::Hit },
+        damage: (damage - defence.armour).max(0.0),
+        damage_type: attack.damage_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn attack(damage: f32, damage_type: DamageType, crit_chance: f32) -> Attack {
+        Attack {
+            damage,
+            damage_type,
+            crit_chance,
+        }
+    }
+
+    #[test]
+    fn resistances_then_armour_cut_the_damage() {
+        let mut rng = Pcg32::seed_from_u64(1);
+        let defence = Defence {
+            armour: 2.0,
+            resistances: Resistances {
+                fire: 0.5,
+                slashing: -0.5,
+                ..Resistances::default()
+            },
+            evasion: 0.0,
+        };
+        let burn = resolve_hit(&attack(10.0, DamageType::Fire, 0.0), &defence, &mut rng);
+        assert_eq!(burn.outcome, HitOutcome::Hit);
+        assert!((burn.damage - 3.0).abs() < EPSILON);
+        let cut = resolve_hit(&attack(10.0, DamageType::Slashing, 0.0), &defence, &mut rng);
+        assert!((cut.damage - 13.0).abs() < EPSILON);
+        // armour never heals
+        let graze = resolve_hit(&attack(1.0, DamageType::Blunt, 0.0), &defence, &mut rng);
+        assert_eq!(graze.damage, 0.0);
+    }
+
+    #[test]
+    fn crits_and_misses_follow_their_chances() {
+        let mut rng = Pcg32::seed_from_u64(2);
+        let always_crits = resolve_hit(&attack(10.0, DamageType::Blunt, 1.0), &Defence::default(), &mut rng);
+        assert_eq!(always_crits.outcome, HitOutcome::Critical);
+        assert!((always_crits.damage - 10.0 * CRIT_MULTIPLIER).abs() < EPSILON);
+
+        let untouchable = Defence {
+            evasion: 1.0,
+            ..Defence::default()
+        };
+        let dodged = resolve_hit(&attack(10.0, DamageType::Blunt, 1.0), &untouchable, &mut rng);
+        assert!(dodged.is_miss());
+        assert_eq!(dodged.damage, 0.0);
+
+        let half = Defence {
+            evasion: 0.5,
+            ..Defence::default()
+        };
+        let misses = (0..1000)
+            .filter(|_| resolve_hit(&attack(10.0, DamageType::Ballistic, 0.0), &half, &mut rng).is_miss())
+            .count();
+        assert!((400..600).contains(&misses), "{} misses", misses);
+    }
+}
@@ -0,0 +1,90 @@
+//! What monsters are built from. Humans drop resources when killed, parts cost them,
+//! and whatever's left carries over from day to day.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resources {
+    pub biomass: u32,
+    pub bones: u32,
+}
+
+/// what a new run starts with, enough for a couple of cheap monsters
+pub const STARTING_RESOURCES: Resources = Resources { biomass: 30, bones: 6 };
+
+impl Resources {
+    pub fn can_afford(&self, cost: Resources) -> bool {
+        self.biomass >= cost.biomass && self.bones >= cost.bones
+    }
+
+    /// takes `cost` off, returning false and leaving everything as it was if there isn't enough
+    #[must_use]
+    pub fn spend(&mut self, cost: Resources) -> bool {
+        if !self.can_afford(cost) {
+            return false;
+        }
+        self.biomass -= cost.biomass;
+        self.bones -= cost.bones;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Resources::default()
+    }
+}
+
+impl Add for Resources {
+    type Output = Resources;
+
+    fn add(self, other: Resources) -> Resources {
+        Resources {
+            biomass: self.biomass + other.biomass,
+            bones: self.bones + other.bones,
+        }
+    }
+}
+
+impl AddAssign for Resources {
+    fn add_assign(&mut self, other: Resources) {
+        *self = *self + other;
+    }
+}
+
+impl fmt::Display for Resources {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} biomass, {} bones", self.biomass, self.bones)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resources(biomass: u32, bones: u32) -> Resources {
+        Resources { biomass, bones }
+    }
+
+    #[test]
+    fn spending_needs_enough_of_everything() {
+        let mut stock = resources(10, 2);
+        assert!(!stock.spend(resources(4, 3)));
+        assert_eq!(stock, resources(10, 2));
+        assert!(stock.spend(resources(4, 2)));
+        assert_eq!(stock, resources(6, 0));
+        assert!(stock.can_afford(resources(6, 0)));
+        assert!(!stock.can_afford(resources(7, 0)));
+    }
+
+    #[test]
+    fn resources_add_up() {
+        let mut stock = resources(1, 2);
+        stock += resources(3, 4);
+        assert_eq!(stock, resources(4, 6));
+        assert!(!stock.is_empty());
+        assert!(Resources::default().is_empty());
+        assert_eq!(stock.to_string(), "4 biomass, 6 bones");
+    }
+}
@@ -0,0 +1,211 @@
+//! How humans pick what to shoot at and where to stand, set per archetype in the
+//! human catalogue.
+
+use crate::movement;
+use crate::SCREEN_SIZE;
+use serde::Deserialize;
+
+/// how close to its range a human closes in before stopping to shoot,
+/// so a target stepping back a little doesn't put it out of range again
+pub const PREFERRED_RANGE: f32 = 0.85;
+/// humans that keep their range back off from anything closer than this share of it
+pub const TOO_CLOSE: f32 = 0.5;
+/// how much slower humans walk backwards
+pub const BACKPEDAL_SPEED: f32 = 0.6;
+/// how far round from straight on flankers come at their target
+pub const FLANK_ANGLE: f32 = 0.9;
+
+/// Which target a human goes for.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum Targeting {
+    /// whichever is nearer, a monster or the nest
+    #[default]
+    Closest,
+    /// the monster closest to dying, then the nest once they're all gone
+    LowestHp,
+    /// the monster doing the most damage, then the nest once they're all gone
+    HighestThreat,
+    /// the nest, only turning on monsters that get within range
+    NestFirst,
+}
+
+/// How an archetype fights, everything being optional in the catalogue.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct Tactics {
+    pub targeting: Targeting,
+    /// the share of its hp below which a human runs off the map, 0 to fight to the death
+    pub retreat_below: f32,
+    /// whether it backs off from monsters that get too close, to keep them at range
+    pub keep_range: bool,
+    /// whether these spread out to come at their target from the sides
+    pub flank: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Monster(usize), // index
+    Nest,
+}
+
+/// What a human can tell about a monster when picking a target.
+#[derive(Debug, Clone, Copy)]
+pub struct MonsterInfo {
+    pub distance: f32, // from the human
+    pub hp: f32,
+    pub threat: f32, // how dangerous it has been, see `threat_of`
+}
+
+/// how dangerous a monster looks, its damage going up with every kill
+pub fn threat_of(damage: f32, kills: u32) -> f32 {
+    damage * (1.0 + kills as f32)
+}
+
+/// picks a target by `targeting`, `range` being how far the human can shoot
+pub fn choose_target(targeting: Targeting, monsters: &[MonsterInfo], nest_distance: f32, range: f32) -> Target {
+    let pick = |key: &dyn Fn(&MonsterInfo) -> f32| {
+        (0..monsters.len()).min_by(|&a, &b| key(&monsters[a]).partial_cmp(&key(&monsters[b])).unwrap())
+    };
+    let monster = match targeting {
+        Targeting::Closest => pick(&|monster| monster.distance).filter(|&i| monsters[i].distance <= nest_distance),
+        Targeting::LowestHp => pick(&|monster| monster.hp),
+        Targeting::HighestThreat => pick(&|monster| -monster.threat),
+        Targeting::NestFirst => pick(&|monster| monster.distance).filter(|&i| monsters[i].distance <= range),
+    };
+    monster.map_or(Target::Nest, Target::Monster)
+}
+
+/// whether a human this hurt should give up and run
+pub fn should_retreat(tactics: &Tactics, hp: f32, total_hp: f32) -> bool {
+    hp < tactics.retreat_below * total_hp
+}
+
+/// where a human closing in on `target` heads for. Flankers aim off to one side,
+/// `flank_angle` round from the way they're coming in, so a group ends up spread round it
+pub fn approach_point(
+    pos: mint::Point2<f32>,
+    target: mint::Point2<f32>,
+    range: f32,
+    flank_angle: f32,
+) -> mint::Point2<f32> {
+    if flank_angle == 0.0 {
+        return target;
+    }
+    let coming_from = movement::heading_towards(target, pos);
+    let point = movement::step_along(target, coming_from + flank_angle, range * PREFERRED_RANGE);
+    mint::Point2 {
+        x: point.x.clamp(0.0, SCREEN_SIZE.0),
+        y: point.y.clamp(0.0, SCREEN_SIZE.1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monster::Order;
+    use crate::simulation::{SimEvent, SimInput, Simulation};
+    use crate::test_fixtures::{archetype, human, monster, point};
+    use crate::waves::WaveScript;
+
+    fn info(distance: f32, hp: f32, threat: f32) -> MonsterInfo {
+        MonsterInfo { distance, hp, threat }
+    }
+
+    #[test]
+    fn each_targeting_picks_its_own_monster() {
+        let monsters = [info(100.0, 80.0, 5.0), info(300.0, 20.0, 10.0), info(200.0, 50.0, 40.0)];
+        assert_eq!(choose_target(Targeting::Closest, &monsters, 500.0, 60.0), Target::Monster(0));
+        assert_eq!(choose_target(Targeting::Closest, &monsters, 50.0, 60.0), Target::Nest);
+        assert_eq!(choose_target(Targeting::LowestHp, &monsters, 50.0, 60.0), Target::Monster(1));
+        assert_eq!(choose_target(Targeting::HighestThreat, &monsters, 50.0, 60.0), Target::Monster(2));
+        assert_eq!(choose_target(Targeting::NestFirst, &monsters, 500.0, 60.0), Target::Nest);
+        assert_eq!(choose_target(Targeting::NestFirst, &monsters, 500.0, 150.0), Target::Monster(0));
+        assert_eq!(choose_target(Targeting::LowestHp, &[], 500.0, 60.0), Target::Nest);
+    }
+
+    #[test]
+    fn flankers_come_in_from_the_side() {
+        let straight = approach_point(point(400.0, 300.0), point(200.0, 300.0), 100.0, 0.0);
+        assert_eq!(straight, point(200.0, 300.0));
+        let left = approach_point(point(400.0, 300.0), point(200.0, 300.0), 100.0, FLANK_ANGLE);
+        let right = approach_point(point(400.0, 300.0), point(200.0, 300.0), 100.0, -FLANK_ANGLE);
+        assert!(left.y > 300.0 && right.y < 300.0);
+        assert!((movement::distance(left, point(200.0, 300.0)) - 100.0 * PREFERRED_RANGE).abs() < 0.01);
+    }
+
+    /// a night with two monsters to choose between, the weak one further off, both
+    /// holding their ground so only the human moves
+    fn simulation(tactics: Tactics, human_pos: mint::Point2<f32>, human_hp: f32) -> Simulation {
+        let archetype = archetype(50.0, 1.0, tactics);
+        let mut simulation = Simulation::new(vec![archetype.clone()], WaveScript { nights: Vec::new() }, 1);
+        for &(hp, damage, pos) in &[(100.0, 30.0, point(500.0, 100.0)), (10.0, 5.0, point(500.0, 400.0))] {
+            let mut monster = monster(hp, damage, pos);
+            monster.order = Some(Order::Hold);
+            simulation.add_monster_at(monster);
+        }
+        let mut human = human(&archetype, human_pos);
+        human.hp = human_hp;
+        simulation.add_human(human);
+        simulation
+    }
+
+    fn step(simulation: &mut Simulation) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        simulation.step(&SimInput::default(), &mut events);
+        events
+    }
+
+    #[test]
+    fn humans_go_after_the_target_their_archetype_prefers() {
+        let human_pos = point(700.0, 160.0);
+        let cases = [
+            (Targeting::Closest, Target::Monster(0)),
+            (Targeting::LowestHp, Target::Monster(1)),
+            (Targeting::HighestThreat, Target::Monster(0)),
+            (Targeting::NestFirst, Target::Nest),
+        ];
+        for &(targeting, expected) in &cases {
+            let mut simulation = simulation(Tactics { targeting, ..Tactics::default() }, human_pos, 50.0);
+            step(&mut simulation);
+            assert_eq!(simulation.get_humans()[0].get_target(), Some(expected), "{:?}", targeting);
+        }
+    }
+
+    #[test]
+    fn wounded_humans_run_off_the_map() {
+        let tactics = Tactics {
+            retreat_below: 0.5,
+            ..Tactics::default()
+        };
+        let mut simulation = simulation(tactics, point(700.0, 250.0), 20.0);
+        let mut fled = false;
+        for _ in 0..120 {
+            let events = step(&mut simulation);
+            assert!(!events.iter().any(|event| matches!(event, SimEvent::HumanFired { .. })));
+            if events.contains(&SimEvent::HumanFled { human: 0 }) {
+                fled = true;
+                break;
+            }
+            assert!(simulation.get_humans()[0].get_pos().x >= 700.0);
+        }
+        assert!(fled);
+        assert!(simulation.get_humans().is_empty());
+    }
+
+    #[test]
+    fn humans_keeping_range_back_off_from_close_monsters() {
+        let tactics = Tactics {
+            keep_range: true,
+            ..Tactics::default()
+        };
+        let mut simulation = simulation(tactics, point(560.0, 150.0), 50.0);
+        let start = simulation.get_humans()[0].get_pos();
+        step(&mut simulation);
+        assert!(simulation.get_humans()[0].get_pos().x > start.x);
+
+        // without it they just stand there shooting
+        let mut simulation = self::simulation(Tactics::default(), start, 50.0);
+        step(&mut simulation);
+        assert_eq!(simulation.get_humans()[0].get_pos(), start);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::ability::Ability;
+use crate::economy::Resources;
+use crate::tech_tree::Progress;
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+/// lives in the user data directory and carries over between runs
+pub const JOURNAL_PATH: &str = "/journal.ron";
+
+// how much has to be seen before the journal will guess at a stat
+const HITS_NEEDED: u32 = 3;
+const SECONDS_MOVING_NEEDED: f32 = 1.0;
+const SIGHTINGS_NEEDED: u32 = 3;
+const HITS_TAKEN_NEEDED: u32 = 3;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeadEvidence {
+    pub sightings: u32,
+    pub furthest_sighting: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BodyEvidence {
+    pub hits_survived: u32,
+    pub most_damage_survived: f32,
+    pub deaths: u32,
+    pub least_damage_at_death: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArmsEvidence {
+    pub hits: u32,
+    pub total_damage: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LegsEvidence {
+    #[serde(default)]
+    pub seconds_moving: f32,
+    pub distance_moved: f32,
+}
+
+/// What the player has seen each part do, by part id. The stats are hidden,
+/// so this is all the game will tell them about a part.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    #[serde(default)]
+    heads: BTreeMap<String, HeadEvidence>,
+    #[serde(default)]
+    bodies: BTreeMap<String, BodyEvidence>,
+    #[serde(default)]
+    arms: BTreeMap<String, ArmsEvidence>,
+    #[serde(default)]
+    legs: BTreeMap<String, LegsEvidence>,
+    /// the ids of the abilities seen at work, by part id
+    #[serde(default)]
+    abilities: BTreeMap<String, BTreeSet<String>>,
+    /// what the tech tree unlocks parts by
+    #[serde(default)]
+    progress: Progress,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn record_sighting(&mut self, head_id: &str, distance: f32) {
+        let evidence = self.heads.entry(head_id.to_string()).or_default();
+        evidence.sightings += 1;
+        evidence.furthest_sighting = evidence.furthest_sighting.max(distance);
+    }
+
+    /// `damage_taken` is everything the monster has lost so far, including this hit
+    pub fn record_hit_taken(&mut self, body_id: &str, damage_taken: f32, survived: bool) {
+        let evidence = self.bodies.entry(body_id.to_string()).or_default();
+        if survived {
+            evidence.hits_survived += 1;
+            evidence.most_damage_survived = evidence.most_damage_survived.max(damage_taken);
+        } else {
+            evidence.least_damage_at_death = if evidence.deaths == 0 {
+                damage_taken
+            } else {
+                evidence.least_damage_at_death.min(damage_taken)
+            };
+            evidence.deaths += 1;
+        }
+    }
+
+    pub fn record_hit(&mut self, arms_id: &str, damage: f32) {
+        let evidence = self.arms.entry(arms_id.to_string()).or_default();
+        evidence.hits += 1;
+        evidence.total_damage += damage;
+    }
+
+    pub fn record_movement(&mut self, legs_id: &str, distance: f32, seconds: f32) {
+        let evidence = self.legs.entry(legs_id.to_string()).or_default();
+        evidence.seconds_moving += seconds;
+        evidence.distance_moved += distance;
+    }
+
+    pub fn record_ability(&mut self, part_id: &str, ability: &dyn Ability) {
+        self.abilities
+            .entry(part_id.to_string())
+            .or_default()
+            .insert(ability.get_id().to_string());
+    }
+
+    pub fn has_seen_ability(&self, part_id: &str, ability: &dyn Ability) -> bool {
+        self.abilities
+            .get(part_id)
+            .is_some_and(|seen| seen.contains(ability.get_id()))
+    }
+
+    pub fn record_day_reached(&mut self, day: u16) {
+        self.progress.best_day = self.progress.best_day.max(day);
+    }
+
+    pub fn record_kill(&mut self) {
+        self.progress.kills += 1;
+    }
+
+    pub fn record_build(&mut self, part_ids: &[&str], cost: Resources) {
+        self.progress.record_build(part_ids, cost);
+    }
+
+    pub fn get_progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// adds everything seen in `other`, e.g. a night's worth of evidence
+    pub fn merge(&mut self, other: &Journal) {
+        for (id, seen) in &other.heads {
+            let evidence = self.heads.entry(id.clone()).or_default();
+            evidence.sightings += seen.sightings;
+            evidence.furthest_sighting = evidence.furthest_sighting.max(seen.furthest_sighting);
+        }
+        for (id, seen) in &other.bodies {
+            let evidence = self.bodies.entry(id.clone()).or_default();
+            evidence.hits_survived += seen.hits_survived;
+            evidence.most_damage_survived = evidence.most_damage_survived.max(seen.most_damage_survived);
+            if seen.deaths > 0 {
+                evidence.least_damage_at_death = if evidence.deaths == 0 {
+                    seen.least_damage_at_death
+                } else {
+                    evidence.least_damage_at_death.min(seen.least_damage_at_death)
+                };
+                evidence.deaths += seen.deaths;
+            }
+        }
+        for (id, seen) in &other.arms {
+            let evidence = self.arms.entry(id.clone()).or_default();
+            evidence.hits += seen.hits;
+            evidence.total_damage += seen.total_damage;
+        }
+        for (id, seen) in &other.legs {
+            let evidence = self.legs.entry(id.clone()).or_default();
+            evidence.seconds_moving += seen.seconds_moving;
+            evidence.distance_moved += seen.distance_moved;
+        }
+        for (id, seen) in &other.abilities {
+            self.abilities.entry(id.clone()).or_default().extend(seen.iter().cloned());
+        }
+        self.progress.merge(&other.progress);
+    }
+
+    /// the estimate shown in the builder for a head, if enough has been seen
+    pub fn describe_head(&self, id: &str) -> Option<String> {
+        let evidence = self.heads.get(id).filter(|evidence| evidence.sightings >= SIGHTINGS_NEEDED)?;
+        Some(format!(
+            "Sight: at least {:.0} ({} sightings)",
+            evidence.furthest_sighting, evidence.sightings
+        ))
+    }
+
+    pub fn describe_body(&self, id: &str) -> Option<String> {
+        let evidence = self.bodies.get(id)?;
+        if evidence.deaths > 0 {
+            Some(format!(
+                "Health: about {:.0} ({} hits survived, {} deaths)",
+                evidence.least_damage_at_death, evidence.hits_survived, evidence.deaths
+            ))
+        } else if evidence.hits_survived >= HITS_TAKEN_NEEDED {
+            Some(format!(
+                "Health: more than {:.0} ({} hits survived)",
+                evidence.most_damage_survived, evidence.hits_survived
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn describe_arms(&self, id: &str) -> Option<String> {
+        let evidence = self.arms.get(id).filter(|evidence| evidence.hits >= HITS_NEEDED)?;
+        Some(format!(
+            "Damage: about {:.0} a hit ({} hits)",
+            evidence.total_damage / evidence.hits as f32,
+            evidence.hits
+        ))
+    }
+
+    pub fn describe_legs(&self, id: &str) -> Option<String> {
+        let evidence = self.legs.get(id).filter(|evidence| evidence.seconds_moving >= SECONDS_MOVING_NEEDED)?;
+        Some(format!(
+            "Speed: about {:.0} a second ({:.0} moved)",
+            evidence.distance_moved / evidence.seconds_moving,
+            evidence.distance_moved
+        ))
+    }
+
+    /// a line for each of the part's `abilities` seen at work, if any have been
+    pub fn describe_abilities<A: Ability>(&self, part_id: &str, abilities: &[A]) -> Option<String> {
+        let seen: Vec<String> = abilities
+            .iter()
+            .filter(|&ability| self.has_seen_ability(part_id, ability))
+            .map(|ability| format!("{}: {}", ability.get_name(), ability.get_description()))
+            .collect();
+        if seen.is_empty() {
+            None
+        } else {
+            Some(seen.join("\n"))
+        }
+    }
+}
+
+/// a missing journal is just an empty one
+pub fn load_journal(ctx: &mut Context) -> GameResult<Journal> {
+    if !filesystem::is_file(ctx, JOURNAL_PATH) {
+        return Ok(Journal::new());
+    }
+    let file = filesystem::open(ctx, JOURNAL_PATH)?;
+    ron::de::from_reader(file)
+        .map_err(|error| GameError::ResourceLoadError(format!("{}: {}", JOURNAL_PATH, error)))
+}
+
+pub fn save_journal(ctx: &mut Context, journal: &Journal) -> GameResult {
+    let text = ron::ser::to_string_pretty(journal, ron::ser::PrettyConfig::default())
+        .map_err(|error| GameError::ResourceLoadError(format!("{}: {}", JOURNAL_PATH, error)))?;
+    let mut file = filesystem::create(ctx, JOURNAL_PATH)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
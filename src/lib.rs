@@ -1,4 +1,22 @@
+pub mod ability;
+pub mod behaviour;
+pub mod combat;
+pub mod economy;
+pub mod human_ai;
+pub mod journal;
 pub mod monster;
 pub mod monster_build;
+pub mod movement;
+pub mod navigation;
+pub mod roster;
+pub mod save;
+pub mod simulation;
 pub mod sprite_loader;
+pub mod status;
+pub mod tech_tree;
+#[cfg(test)]
+mod test_fixtures;
+pub mod veterancy;
+pub mod vision;
+pub mod waves;
 pub const SCREEN_SIZE: (f32, f32) = (800.0, 600.0);
@@ -1,11 +1,16 @@
-use ggez;
-
-use ggez::event::{self, KeyCode, KeyMods};
+use ggez::event::{self, KeyCode, KeyMods, MouseButton};
 use ggez::graphics;
-use ggez::{audio, Context, GameResult};
-use monster_nest_creator::monster::AttackState;
+use ggez::{audio, input, timer, Context, GameResult};
+use monster_nest_creator::economy::{Resources, STARTING_RESOURCES};
+use monster_nest_creator::journal::{self, Journal};
+use monster_nest_creator::monster::{AttackState, CONTROL_GROUPS};
 use monster_nest_creator::monster_build::BuilderState;
+use monster_nest_creator::roster::{self, RosterState};
+use monster_nest_creator::save::{self, SaveGame};
+use monster_nest_creator::simulation::TICKS_PER_SECOND;
 use monster_nest_creator::sprite_loader::*;
+use monster_nest_creator::tech_tree::{load_tech_tree, TECH_TREE_PATH};
+use monster_nest_creator::waves::{load_wave_script, WAVE_SCRIPT_PATH};
 use monster_nest_creator::SCREEN_SIZE;
 use std::env;
 use std::path;
@@ -14,10 +19,14 @@ use audio::SoundSource;
 enum ScreenState { // TODO: make stuff look better (somehow)
     MainMenu,
     MonsterCreation, // player building their monster
+    Roster,          // looking after the monsters before the night
     NightAttack,     // humans attack the 'nest'
     EndGame,
 }
 
+const MENU_OPTIONS: [&str; 2] = ["New game", "Continue"];
+const MENU_CONTINUE: usize = 1;
+
 // contains the game's state
 struct MainState {
     frames_modulo: usize,
@@ -26,15 +35,26 @@ struct MainState {
     title: graphics::Text,
     title_img: graphics::Image,
     title_text: graphics::Text,
+    menu_choice: usize,
+    has_save: bool,
     transition_sound: audio::Source,
+    catalogue: PartCatalogue, // kept whole to rebuild saved monsters
+    journal: Journal,         // what's been learnt about the parts, across every run
     builder_state: BuilderState,
+    roster_state: RosterState,
     attack_state: AttackState,
     day: u16,
     won: bool,
+    seed: u64,             // everything random in a run comes from this
+    seed_arg: Option<u64>, // from --seed, which every new run then uses
+    resources: Resources, // what's left to build monsters with
+    harvest: Resources,   // gathered last night
+    built_today: bool,    // resting is only for days nothing's been built
 }
 
 impl MainState {
-    fn new(ctx: &mut Context) -> GameResult<MainState> {
+    fn new(ctx: &mut Context, seed_arg: Option<u64>) -> GameResult<MainState> {
+        let seed = seed_arg.unwrap_or_else(rand::random);
         // The ttf file will be in your resources directory. Later, we
         // will mount that directory so we can omit it in the path here.
         let font = graphics::Font::new(ctx, "/fonts/Alata-Regular.ttf")?; // REVIEW: replace with scary font?
@@ -50,8 +70,8 @@ impl MainState {
                 "{}{}{}{}",
                 "Create your monsters in the day, but beware,\n",
                 "humans will attack your nest in the night!\n\n",
-                "Press enter to continue...\n\n",
-                "(Note: there is audio in this game)"
+                "(Note: there is audio in this game)\n\n",
+                "Use the arrow keys and enter to choose:"
             ),
             color: Some(graphics::BLACK),
             font: Some(font),
@@ -67,6 +87,14 @@ impl MainState {
         ];
 
         let tree_sprite = graphics::Image::new(ctx, "/sprites/tree.png")?;
+        let catalogue = load_part_catalogue(ctx, PART_CATALOGUE_PATH)?;
+        let humans = load_human_catalogue(ctx, HUMAN_CATALOGUE_PATH)?;
+        let waves = load_wave_script(ctx, WAVE_SCRIPT_PATH, &humans.archetypes)?;
+        let tech_tree = load_tech_tree(ctx, TECH_TREE_PATH, &catalogue)?;
+        let journal = journal::load_journal(ctx).unwrap_or_else(|error| {
+            eprintln!("Could not read the journal, starting a new one: {}", error);
+            Journal::new()
+        });
 
         let s = MainState {
             frames_modulo: 0,
@@ -75,21 +103,36 @@ impl MainState {
             title,
             title_img: main_img,
             title_text,
+            menu_choice: 0,
+            has_save: save::has_save(ctx),
             transition_sound,
             builder_state: BuilderState::new(
-                get_heads(ctx),
-                get_bodies(ctx),
-                get_arms(ctx),
-                get_legs(ctx),
+                catalogue.heads.clone(),
+                catalogue.bodies.clone(),
+                catalogue.arms.clone(),
+                catalogue.legs.clone(),
+                catalogue.sprites.clone(),
+                tech_tree,
             ),
+            roster_state: RosterState::new(),
             attack_state: AttackState::new(
-                get_human_sprites(ctx),
+                humans,
+                waves,
+                catalogue.sprites.clone(),
                 gunshot_sound,
                 hit_sounds,
                 tree_sprite,
+                seed,
             ),
+            catalogue,
+            journal,
             day: 1,
             won: false,
+            seed,
+            seed_arg,
+            resources: STARTING_RESOURCES,
+            harvest: Resources::default(),
+            built_today: false,
         };
         Ok(s)
     }
@@ -97,6 +140,62 @@ impl MainState {
     fn switch_state(&mut self, new_state: ScreenState) {
         self.state = new_state;
     }
+
+    fn new_game(&mut self) {
+        // a fresh run each time, unless one was asked for
+        self.seed = self.seed_arg.unwrap_or_else(rand::random);
+        println!("Seed: {}", self.seed);
+        self.day = 1;
+        self.won = false;
+        self.resources = STARTING_RESOURCES;
+        self.harvest = Resources::default();
+        self.journal.record_day_reached(self.day);
+        self.built_today = false;
+        self.builder_state.empty_choices();
+        self.attack_state.reset(self.seed);
+        self.switch_state(ScreenState::MonsterCreation);
+    }
+
+    fn continue_game(&mut self, ctx: &mut Context) -> GameResult {
+        let save = save::load_game(ctx)?;
+        let monsters = save.get_monsters(&self.catalogue)?;
+
+        self.day = save.day;
+        self.won = false;
+        self.seed = save.seed;
+        self.resources = save.resources;
+        self.harvest = Resources::default();
+        self.built_today = false;
+        self.builder_state.empty_choices();
+        self.attack_state.restore(save.seed, save.rng, monsters);
+        self.attack_state.reset_monster_pos();
+        self.switch_state(ScreenState::MonsterCreation);
+        Ok(())
+    }
+
+    /// saves the run as it stands at dawn, before monsters are moved back to the nest
+    fn save_game(&mut self, ctx: &mut Context) {
+        let save = SaveGame::capture(self.day, self.seed, self.resources, self.attack_state.get_simulation());
+        match save::save_game(ctx, &save) {
+            Ok(()) => self.has_save = true,
+            Err(error) => eprintln!("Could not save the game: {}", error),
+        }
+    }
+
+    fn save_journal(&self, ctx: &mut Context) {
+        if let Err(error) = journal::save_journal(ctx, &self.journal) {
+            eprintln!("Could not save the journal: {}", error);
+        }
+    }
+
+    /// a finished run can't be continued
+    fn end_game(&mut self, ctx: &mut Context) {
+        if let Err(error) = save::delete_save(ctx) {
+            eprintln!("Could not delete the save: {}", error);
+        }
+        self.has_save = false;
+        self.switch_state(ScreenState::EndGame);
+    }
 }
 
 // Then we implement the `ggez:event::EventHandler` trait on it, which
@@ -106,34 +205,49 @@ impl MainState {
 // that you can override if you wish, but the defaults are fine.
 impl event::EventHandler for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        match self.state {
-            ScreenState::NightAttack => {
-                if let Some(check_win) = self.attack_state.update_state(ctx) {
-                    if check_win && self.day == 5 {
+        // the night steps at a fixed rate, catching up or waiting on the frame rate as needed
+        while timer::check_update_time(ctx, TICKS_PER_SECOND) {
+            if let ScreenState::NightAttack = self.state {
+                if let Some(check_win) = self.attack_state.update_state() {
+                    let evidence = self.attack_state.get_simulation_mut().take_evidence();
+                    self.journal.merge(&evidence);
+                    if check_win {
+                        self.journal.record_day_reached(self.day + 1);
+                    }
+                    self.save_journal(ctx);
+
+                    let last_night = self.attack_state.get_simulation().get_night_count();
+                    if check_win && usize::from(self.day) >= last_night {
                         self.won = true;
                         if let Err(error) = self.transition_sound.play() {
                             eprintln!("{}", error);
                         }
-                        self.switch_state(ScreenState::EndGame);
+                        self.end_game(ctx);
                     } else if !check_win {
-                        self.switch_state(ScreenState::EndGame);
+                        self.end_game(ctx);
                     } else {
                         // move on to next day
                         self.day += 1;
+                        self.harvest = self.attack_state.get_simulation_mut().take_harvest();
+                        self.resources += self.harvest;
+                        self.built_today = false;
+                        self.attack_state.get_simulation_mut().recover_at_dawn();
                         if let Err(error) = self.transition_sound.play() {
                             eprintln!("{}", error);
                         }
+                        self.save_game(ctx);
                         self.attack_state.reset_monster_pos();
 
                         self.switch_state(ScreenState::MonsterCreation);
                     }
                 }
             }
-            _ => (),
         }
         Ok(())
     }
 
+    // the FPS check keeps `% 100`, as `is_multiple_of` needs a newer Rust than the crate asks for
+    #[allow(clippy::manual_is_multiple_of)]
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         // Drawables are drawn from their top-left corner.
         match self.state {
@@ -152,6 +266,21 @@ impl event::EventHandler for MainState {
                 };
                 graphics::draw(ctx, &self.title_text, (title_text_dest_point,))?;
 
+                for (i, option) in MENU_OPTIONS.iter().enumerate() {
+                    let available = i != MENU_CONTINUE || self.has_save;
+                    let option_text = graphics::Text::new(graphics::TextFragment {
+                        text: format!("{} {}", if i == self.menu_choice { ">" } else { " " }, option),
+                        color: Some(if available { graphics::BLACK } else { graphics::Color::from_rgb(140, 110, 110) }),
+                        font: Some(self.font),
+                        scale: Some(graphics::Scale { x: 20.0, y: 20.0 }),
+                    });
+                    let option_dest_point = mint::Point2 {
+                        x: (SCREEN_SIZE.0 / 2.0 - 150.0),
+                        y: (SCREEN_SIZE.1 / 2.0 + 90.0 + i as f32 * 25.0),
+                    };
+                    graphics::draw(ctx, &option_text, (option_dest_point,))?;
+                }
+
                 let scale_vec = [2.0, 2.0];
                 let img_dest_point = mint::Point2 {
                     x: self.frames_modulo as f32,
@@ -177,12 +306,74 @@ impl event::EventHandler for MainState {
                     }),
                     (day_dest_point,),
                 )?;
-                self.builder_state.draw(ctx, self.font)?;
+                if !self.harvest.is_empty() {
+                    let harvest_dest_point = mint::Point2 { x: 10.0, y: 110.0 };
+                    graphics::draw(
+                        ctx,
+                        &graphics::Text::new(graphics::TextFragment {
+                            text: format!("Gathered {} from the dead.", self.harvest),
+                            color: Some(graphics::BLACK),
+                            font: Some(self.font),
+                            scale: Some(graphics::Scale { x: 20.0, y: 20.0 }),
+                        }),
+                        (harvest_dest_point,),
+                    )?;
+                }
+                let monsters = self.attack_state.get_simulation().get_monsters();
+                roster::draw_health_summary(ctx, self.font, monsters, mint::Point2 { x: 10.0, y: 140.0 })?;
+                if monsters.is_empty() {
+                    let warning = if self.builder_state.can_afford_a_monster(self.resources, &self.journal) {
+                        "Build a monster first,\nthe nest can't guard\nitself tonight"
+                    } else {
+                        "Nothing left to build\nwith, so this run\nis over"
+                    };
+                    let warning_dest_point = mint::Point2 { x: SCREEN_SIZE.0 - 230.0, y: 200.0 };
+                    graphics::draw(
+                        ctx,
+                        &graphics::Text::new(graphics::TextFragment {
+                            text: warning.to_string(),
+                            color: Some(graphics::Color::from_rgb(200, 0, 0)),
+                            font: Some(self.font),
+                            scale: Some(graphics::Scale { x: 18.0, y: 18.0 }),
+                        }),
+                        (warning_dest_point,),
+                    )?;
+                }
+                if !self.built_today && !monsters.is_empty() {
+                    let rest_dest_point = mint::Point2 { x: SCREEN_SIZE.0 - 230.0, y: 200.0 };
+                    graphics::draw(
+                        ctx,
+                        &graphics::Text::new(graphics::TextFragment {
+                            text: "R to rest today instead,\nhealing every monster".to_string(),
+                            color: Some(graphics::BLACK),
+                            font: Some(self.font),
+                            scale: Some(graphics::Scale { x: 18.0, y: 18.0 }),
+                        }),
+                        (rest_dest_point,),
+                    )?;
+                }
+                self.builder_state.draw(ctx, self.font, &self.journal, self.resources)?;
+            }
+            ScreenState::Roster => {
+                graphics::clear(ctx, graphics::WHITE);
+
+                self.roster_state.draw(ctx, self.font, self.attack_state.get_simulation().get_monsters())?;
             }
             ScreenState::NightAttack => {
                 graphics::clear(ctx, graphics::Color::from_rgb(166, 166, 166));
 
                 self.attack_state.draw(ctx)?;
+
+                if let Some(time_left) = self.attack_state.get_simulation().get_time_left() {
+                    let timer_text = graphics::Text::new(graphics::TextFragment {
+                        text: format!("Dawn in {:.0}s", time_left.ceil()),
+                        color: Some(graphics::WHITE),
+                        font: Some(self.font),
+                        scale: Some(graphics::Scale { x: 24.0, y: 24.0 }),
+                    });
+                    let timer_dest_point = mint::Point2 { x: SCREEN_SIZE.0 - 150.0, y: 40.0 };
+                    graphics::draw(ctx, &timer_text, (timer_dest_point,))?;
+                }
             }
             ScreenState::EndGame => {
                 graphics::clear(ctx, graphics::WHITE);
@@ -196,8 +387,13 @@ impl event::EventHandler for MainState {
                 } else {
                     graphics::Text::new(graphics::TextFragment {
                         text: format!(
-                            "All your monsters died!\nYou survived until day {}.\n\nPress enter to restart.",
-                            self.day
+                            "{}\nYou survived until day {}.\n\nPress enter to restart.\n(seed {})",
+                            if self.attack_state.get_simulation().get_nest().is_destroyed() {
+                                "Your nest was destroyed!"
+                            } else {
+                                "All your monsters died!"
+                            },
+                            self.day, self.seed
                         ),
                         color: Some(graphics::Color::from_rgb(255, 0, 0)),
                         font: Some(self.font),
@@ -215,7 +411,7 @@ impl event::EventHandler for MainState {
 
         self.frames_modulo = (self.frames_modulo + 1) % SCREEN_SIZE.0 as usize;
         if (self.frames_modulo % 100) == 0 {
-            println!("FPS: {}", ggez::timer::fps(ctx));
+            println!("FPS: {}", timer::fps(ctx));
         }
 
         Ok(())
@@ -225,13 +421,25 @@ impl event::EventHandler for MainState {
         &mut self,
         ctx: &mut Context,
         keycode: KeyCode,
-        _keymod: KeyMods,
+        keymod: KeyMods,
         _repeat: bool,
     ) {
         match self.state {
             ScreenState::MainMenu => match keycode {
+                KeyCode::Up => self.menu_choice = self.menu_choice.saturating_sub(1),
+                KeyCode::Down => self.menu_choice = (self.menu_choice + 1).min(MENU_OPTIONS.len() - 1),
                 KeyCode::Return => {
-                    self.switch_state(ScreenState::MonsterCreation);
+                    if self.menu_choice == MENU_CONTINUE {
+                        if !self.has_save {
+                            return;
+                        }
+                        if let Err(error) = self.continue_game(ctx) {
+                            eprintln!("Could not continue the saved game: {}", error);
+                            self.has_save = false;
+                        }
+                    } else {
+                        self.new_game();
+                    }
                 },
                 KeyCode::Escape => event::quit(ctx),
                 _ => (),
@@ -240,38 +448,136 @@ impl event::EventHandler for MainState {
                 KeyCode::Right => self.builder_state.move_option(true),
                 KeyCode::Left => self.builder_state.move_option(false),
                 KeyCode::Return => {
-                    self.builder_state.choose_current_and_reset();
+                    self.builder_state.choose_current_and_reset(self.resources, &self.journal);
                     if self.builder_state.is_fully_selected() {
-                        let (head, body, arms, legs) = self.builder_state.get_built_monster();
+                        // only affordable parts can be chosen, so this can't fail
+                        let cost = self.builder_state.get_spent();
+                        let spent = self.resources.spend(cost);
+                        debug_assert!(spent, "built a monster costing {} with only {}", cost, self.resources);
+                        let (head, body, arms, legs, behaviour) = self.builder_state.get_built_monster();
+                        self.journal
+                            .record_build(&[head.get_id(), body.get_id(), arms.get_id(), legs.get_id()], cost);
+                        self.save_journal(ctx);
                         self.builder_state.empty_choices();
-                        self.attack_state.add_monster(head, body, arms, legs);
-
-                        self.attack_state.generate_humans(self.day);
-                        self.attack_state.generate_scenery();
-                        self.switch_state(ScreenState::NightAttack);
+                        self.attack_state.add_monster(head, body, arms, legs, behaviour);
+                        self.built_today = true;
                     }
                 }
+                KeyCode::R if !self.built_today && !self.attack_state.get_simulation().get_monsters().is_empty() => {
+                    // a day off building, so straight on to the roster
+                    self.builder_state.empty_choices();
+                    self.attack_state.get_simulation_mut().rest_monsters();
+                    self.roster_state.open();
+                    self.switch_state(ScreenState::Roster);
+                }
+                KeyCode::Space => {
+                    // a night with nobody to defend the nest is lost before it starts
+                    if self.attack_state.get_simulation().get_monsters().is_empty()
+                        && self.builder_state.can_afford_a_monster(self.resources, &self.journal)
+                    {
+                        return;
+                    }
+                    // done building for the day, dropping any half-built monster
+                    self.builder_state.empty_choices();
+                    self.roster_state.open();
+                    self.switch_state(ScreenState::Roster);
+                }
                 KeyCode::Escape => event::quit(ctx),
                 _ => (),
             },
+            ScreenState::Roster => {
+                if keycode == KeyCode::Escape && !self.roster_state.is_busy() {
+                    event::quit(ctx);
+                } else if self.roster_state.key_down(keycode, self.attack_state.get_simulation_mut()) {
+                    self.attack_state.generate_scenery();
+                    self.attack_state.start_night(self.day);
+                    self.switch_state(ScreenState::NightAttack);
+                }
+            }
             ScreenState::NightAttack => match keycode {
-                // KeyCode::Down => self.attack_state.move_monster_down(),
-                // KeyCode::Up => self.attack_state.move_monster_up(),
-                // KeyCode::Left => self.attack_state.move_monster_left(),
-                // KeyCode::Right => self.attack_state.move_monster_right(),
+                KeyCode::H => self.attack_state.hold_selected(),
+                KeyCode::R => self.attack_state.release_selected(),
+                KeyCode::F3 => self.attack_state.toggle_route_overlay(),
                 KeyCode::Escape => event::quit(ctx),
-                _ => (),
+                _ => {
+                    if let Some(group) = control_group_of(keycode) {
+                        if keymod.contains(KeyMods::CTRL) {
+                            self.attack_state.assign_control_group(group);
+                        } else {
+                            self.attack_state.select_control_group(group);
+                        }
+                    }
+                }
             },
             ScreenState::EndGame => match keycode {
                 KeyCode::Return => {
+                    self.menu_choice = 0;
                     self.switch_state(ScreenState::MainMenu);
-                    self.day = 1;
                 },
                 KeyCode::Escape => event::quit(ctx),
                 _ => (),
             },
         }
     }
+
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if let ScreenState::NightAttack = self.state {
+            match button {
+                MouseButton::Left => self.attack_state.begin_selection(mint::Point2 { x, y }),
+                MouseButton::Right => self.attack_state.order_selected(mint::Point2 { x, y }),
+                _ => (),
+            }
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if let (ScreenState::NightAttack, MouseButton::Left) = (&self.state, button) {
+            let add = input::keyboard::is_mod_active(ctx, KeyMods::SHIFT);
+            self.attack_state.end_selection(mint::Point2 { x, y }, add);
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        if let ScreenState::NightAttack = self.state {
+            self.attack_state.drag_selection(mint::Point2 { x, y });
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        if let ScreenState::Roster = self.state {
+            self.roster_state.text_input(character);
+        }
+    }
+}
+
+/// the control group a number key stands for, 1 being the first
+fn control_group_of(keycode: KeyCode) -> Option<usize> {
+    let keys = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    keys[..CONTROL_GROUPS].iter().position(|&key| key == keycode)
+}
+
+/// reads `--seed <number>` from the command line, if given
+fn get_seed_arg() -> Option<u64> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            match args.next().map(|value| value.parse()) {
+                Some(Ok(seed)) => return Some(seed),
+                _ => eprintln!("--seed expects a number, using a random seed instead"),
+            }
+        }
+    }
+    None
 }
 
 // Now our main function, which does three things:
@@ -303,6 +609,6 @@ pub fn main() -> GameResult {
         .window_mode(ggez::conf::WindowMode::default().dimensions(SCREEN_SIZE.0, SCREEN_SIZE.1));
     let (ctx, event_loop) = &mut cb.build()?;
 
-    let state = &mut MainState::new(ctx)?;
+    let state = &mut MainState::new(ctx, get_seed_arg())?;
     event::run(ctx, event_loop, state)
 }
@@ -1,247 +1,980 @@
-use crate::monster_build::{Arms, Body, Head, Legs, Sprite};
-use crate::SCREEN_SIZE;
+use crate::behaviour::Behaviour;
+use crate::combat::{Attack, DamageType, Defence, HitOutcome, HitResult, Resistances};
+use crate::economy::Resources;
+use crate::human_ai::{self, Tactics, Target};
+use crate::monster_build::{Arms, Body, Head, Legs, PartSprites, Sprite};
+use crate::movement;
+use crate::navigation::Route;
+use crate::simulation::{SimEvent, SimInput, Simulation, TICK_SECONDS};
+use crate::sprite_loader::HumanCatalogue;
+use crate::status::{StatusEffect, StatusKind, Statuses};
+use crate::veterancy::{self, LevelBonus};
+use crate::vision::ViewCone;
+use crate::waves::WaveScript;
 use audio::SoundSource;
 use ggez::{
     audio,
     graphics::{self, Image},
-    Context, GameResult, input,
+    Context, GameResult,
 };
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::Deserialize;
 use std::f32::consts::PI;
 
-const FRAMES_TO_WIN_STAGE: usize = 30*60;
+// the size of a monster on the night map, drawn at half scale
+pub const MONSTER_WIDTH: f32 = 32.0;
+pub const MONSTER_HEIGHT: f32 = 96.0;
+pub const HUMAN_SIZE: f32 = 32.0;
 
 pub struct Monster {
-    head: Head,
-    body: Body,
-    arms: Arms,
-    legs: Legs,
-    pos: mint::Point2<f32>,
-    hp: f32,
-    cooldown: u32,
+    pub(crate) name: String,
+    pub(crate) head: Head,
+    pub(crate) body: Body,
+    pub(crate) arms: Arms,
+    pub(crate) legs: Legs,
+    pub(crate) behaviour: &'static dyn Behaviour, // what it does when left to itself
+    pub(crate) pos: mint::Point2<f32>,
+    pub(crate) heading: f32, // see `movement`, monsters start off facing right towards the humans
+    pub(crate) hp: f32,
+    pub(crate) kills: u32,
+    pub(crate) xp: f32,       // see `veterancy`
+    pub(crate) cooldown: f32, // seconds until it can hit again
+    pub(crate) has_target: bool, // whether a human was in sight last tick
+    pub(crate) route: Route,
+    pub(crate) order: Option<Order>, // left to its own devices if None
+    pub(crate) stalled: u32,         // ticks in a row a move order hasn't got it anywhere
+    pub(crate) statuses: Statuses,
+}
+
+/// What the player has told a monster to do, in place of what it would do by itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    /// walk until the monster's center gets to the point, ignoring any humans on the way
+    Move(mint::Point2<f32>),
+    /// go after this human, by index, until one of them is dead
+    Attack(usize),
+    /// stay put, only fighting humans that come within reach
+    Hold,
 }
 
 impl Monster {
-    pub fn new(head: Head, body: Body, arms: Arms, legs: Legs, pos: mint::Point2<f32>) -> Self {
+    pub fn new(
+        name: String,
+        head: Head,
+        body: Body,
+        arms: Arms,
+        legs: Legs,
+        behaviour: &'static dyn Behaviour,
+        pos: mint::Point2<f32>,
+    ) -> Self {
         let hp = body.get_health();
-        Self {
+        let mut monster = Self {
+            name,
             head,
             body,
             arms,
             legs,
+            behaviour,
             pos,
+            heading: 0.0,
             hp,
-            cooldown: 0,
+            kills: 0,
+            xp: 0.0,
+            cooldown: 0.0,
+            has_target: false,
+            route: Route::default(),
+            order: None,
+            stalled: 0,
+            statuses: Statuses::default(),
+        };
+        monster.reset_statuses();
+        monster
+    }
+
+    /// wipes off anything from a previous fight, leaving only what its parts give it
+    pub(crate) fn reset_statuses(&mut self) {
+        self.statuses.clear();
+        let regeneration = self.body.get_regeneration();
+        if regeneration > 0.0 {
+            self.statuses.apply(StatusEffect {
+                kind: StatusKind::Regeneration,
+                strength: regeneration,
+                duration: f32::INFINITY,
+                source: None,
+            });
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_head(&self) -> &Head {
+        &self.head
+    }
+
+    pub fn get_body(&self) -> &Body {
+        &self.body
+    }
+
+    pub fn get_arms(&self) -> &Arms {
+        &self.arms
+    }
+
+    pub fn get_legs(&self) -> &Legs {
+        &self.legs
+    }
+
+    pub fn get_behaviour(&self) -> &'static dyn Behaviour {
+        self.behaviour
+    }
+
+    pub fn get_pos(&self) -> mint::Point2<f32> {
+        self.pos
+    }
+
+    pub fn get_center(&self) -> mint::Point2<f32> {
+        mint::Point2 {
+            x: self.pos.x + MONSTER_WIDTH / 2.0,
+            y: self.pos.y + MONSTER_HEIGHT / 2.0,
+        }
+    }
+
+    pub fn get_hitbox(&self) -> graphics::Rect {
+        monster_hitbox_at(self.pos)
+    }
+
+    pub fn get_heading(&self) -> f32 {
+        self.heading
+    }
+
+    /// what the monster can see from its head, facing the way it's heading
+    pub fn get_view_cone(&self) -> ViewCone {
+        ViewCone {
+            eye: self.get_center(),
+            heading: self.heading,
+            field_of_view: self.head.get_field_of_view(),
+            range: self.get_sight_range(),
+        }
+    }
+
+    pub fn get_hp(&self) -> f32 {
+        self.hp
+    }
+
+    pub fn get_total_hp(&self) -> f32 {
+        self.body.get_health() * self.get_bonus().health
+    }
+
+    pub fn get_kills(&self) -> u32 {
+        self.kills
+    }
+
+    pub fn get_xp(&self) -> f32 {
+        self.xp
+    }
+
+    pub fn get_level(&self) -> u32 {
+        veterancy::get_level(self.xp)
+    }
+
+    /// what its level multiplies its parts' stats by
+    pub fn get_bonus(&self) -> LevelBonus {
+        LevelBonus::for_level(self.get_level())
+    }
+
+    /// takes the `stat` its level boosts back off `value`, for the journal, which is about
+    /// the parts rather than how seasoned the monster wearing them is
+    pub(crate) fn without_bonus(&self, value: f32, stat: impl Fn(&LevelBonus) -> f32) -> f32 {
+        value / stat(&self.get_bonus())
+    }
+
+    /// returns true if it's gone up a level, gaining the extra hp with it
+    pub(crate) fn gain_xp(&mut self, xp: f32) -> bool {
+        let level = self.get_level();
+        let total_hp = self.get_total_hp();
+        self.xp += xp;
+        if self.get_level() == level {
+            return false;
+        }
+        self.hp += self.get_total_hp() - total_hp;
+        true
+    }
+
+    /// heals `share` of its total hp, never past full
+    pub(crate) fn recover(&mut self, share: f32) {
+        self.hp = (self.hp + self.get_total_hp() * share).min(self.get_total_hp());
+    }
+
+    /// its arms' attack, hitting harder the higher its level
+    pub fn get_attack(&self) -> Attack {
+        let attack = self.arms.get_attack();
+        Attack {
+            damage: attack.damage * self.get_bonus().damage,
+            ..attack
+        }
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        self.legs.get_speed() * self.get_bonus().speed
+    }
+
+    pub fn get_sight_range(&self) -> f32 {
+        self.head.get_sight_range() * self.get_bonus().sight
+    }
+
+    /// how well its parts stand up to being hit
+    pub fn get_defence(&self) -> Defence {
+        Defence {
+            armour: self.body.get_armour(),
+            resistances: self.body.get_resistances(),
+            evasion: self.legs.get_evasion(),
+        }
+    }
+
+    pub fn get_route(&self) -> &Route {
+        &self.route
+    }
+
+    pub fn get_order(&self) -> Option<Order> {
+        self.order
+    }
+
+    pub fn get_statuses(&self) -> &Statuses {
+        &self.statuses
+    }
+}
+
+pub const NEST_SIZE: f32 = 64.0;
+const NEST_HP: f32 = 300.0;
+
+/// Where the monsters live, on the left of the night map. Humans will try to wreck it.
+pub struct Nest {
+    pub(crate) pos: mint::Point2<f32>, // top-left corner
+    pub(crate) total_hp: f32,
+    pub(crate) hp: f32,
+}
+
+impl Nest {
+    pub fn new(pos: mint::Point2<f32>) -> Self {
+        Self {
+            pos,
+            total_hp: NEST_HP,
+            hp: NEST_HP,
+        }
+    }
+
+    pub fn get_center(&self) -> mint::Point2<f32> {
+        mint::Point2 {
+            x: self.pos.x + NEST_SIZE / 2.0,
+            y: self.pos.y + NEST_SIZE / 2.0,
         }
     }
+
+    pub fn get_hp(&self) -> f32 {
+        self.hp
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.hp <= 0.0
+    }
+
+    /// the monsters patch it up during the day
+    pub fn repair(&mut self) {
+        self.hp = self.total_hp;
+    }
+}
+
+/// A kind of human, as described in the human catalogue. Stats are (min, max) ranges
+/// that every human of this kind rolls within.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Archetype {
+    pub id: String,
+    pub name: String,
+    pub sprite: String,
+    pub tint: (u8, u8, u8),
+    /// pixels per second
+    pub speed: (f32, f32),
+    pub range: (f32, f32),
+    pub hp: (f32, f32),
+    pub damage: (f32, f32),
+    /// radians the human can turn per second
+    pub turn_rate: f32,
+    /// seconds between shots
+    pub reload: f32,
+    /// pixels per second
+    pub bullet_speed: f32,
+    /// radians either side of the aim a shot can stray
+    pub spread: f32,
+    /// how much of a night's budget one of these uses up
+    pub cost: u32,
+    pub first_day: u16,
+    pub weight: u32,
+    #[serde(default)]
+    pub tactics: Tactics,
+    /// left on whatever their bullets hit
+    #[serde(default)]
+    pub on_hit: Option<StatusEffect>,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// the chance, from 0 to 1, of a shot doing double damage
+    #[serde(default)]
+    pub crit_chance: f32,
+    /// taken off every hit, after resistances
+    #[serde(default)]
+    pub armour: f32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// the chance, from 0 to 1, of a hit missing them
+    #[serde(default)]
+    pub evasion: f32,
+    /// what's left to gather when one is killed
+    #[serde(default)]
+    pub drops: Resources,
 }
 
 pub struct Human {
-    sprite_index: usize,
-    tilt: f32,
-    pos: mint::Point2<f32>,
-    speed: f32,
-    range: f32,
-    total_hp: f32,
-    hp: f32,
-    damage: f32,
-    // the number of frames left until attack is available (game runs at ~70fps):
-    cooldown: u32,
+    pub(crate) archetype: usize,
+    pub(crate) heading: f32, // see `movement`, humans start off facing left
+    pub(crate) pos: mint::Point2<f32>,
+    pub(crate) speed: f32,
+    pub(crate) range: f32,
+    pub(crate) total_hp: f32,
+    pub(crate) hp: f32,
+    pub(crate) attack: Attack, // what each of its shots does
+    pub(crate) defence: Defence,
+    pub(crate) turn_rate: f32,
+    pub(crate) reload: f32,
+    pub(crate) bullet_speed: f32,
+    pub(crate) spread: f32,
+    pub(crate) cooldown: f32, // seconds until it can shoot again
+    pub(crate) route: Route,
+    pub(crate) target: Option<Target>, // what it went for last tick
+    pub(crate) flank_angle: f32, // which way round it comes at its target, 0 head on
+    pub(crate) on_hit: Option<StatusEffect>,
+    pub(crate) statuses: Statuses,
 }
 
 impl Human {
+    /// rolls a new human of the given archetype, `archetype_index` being its place in the catalogue
+    pub fn new<R: Rng>(
+        archetype_index: usize,
+        archetype: &Archetype,
+        pos: mint::Point2<f32>,
+        rng: &mut R,
+    ) -> Self {
+        let hp = roll(rng, archetype.hp);
+        let flank_angle = if archetype.tactics.flank {
+            // each picks a side, so a group ends up coming in from both
+            if rng.gen() { human_ai::FLANK_ANGLE } else { -human_ai::FLANK_ANGLE }
+        } else {
+            0.0
+        };
+        Self {
+            archetype: archetype_index,
+            heading: PI,
+            pos,
+            speed: roll(rng, archetype.speed),
+            range: roll(rng, archetype.range),
+            total_hp: hp,
+            hp,
+            attack: Attack {
+                damage: roll(rng, archetype.damage),
+                damage_type: archetype.damage_type,
+                crit_chance: archetype.crit_chance,
+            },
+            defence: Defence {
+                armour: archetype.armour,
+                resistances: archetype.resistances,
+                evasion: archetype.evasion,
+            },
+            turn_rate: archetype.turn_rate,
+            reload: archetype.reload,
+            bullet_speed: archetype.bullet_speed,
+            spread: archetype.spread,
+            cooldown: 0.0,
+            route: Route::default(),
+            target: None,
+            flank_angle,
+            on_hit: archetype.on_hit,
+            statuses: Statuses::default(),
+        }
+    }
+
+    /// multiplies its hp and damage, see `veterancy::get_difficulty_factor`
+    pub(crate) fn toughen(&mut self, factor: f32) {
+        self.total_hp *= factor;
+        self.hp *= factor;
+        self.attack.damage *= factor;
+    }
+
+    pub fn get_pos(&self) -> mint::Point2<f32> {
+        self.pos
+    }
+
+    pub fn get_hp(&self) -> f32 {
+        self.hp
+    }
+
+    pub fn get_archetype(&self) -> usize {
+        self.archetype
+    }
+
+    pub fn get_hitbox(&self) -> graphics::Rect {
+        human_hitbox_at(self.pos)
+    }
+
+    pub fn get_route(&self) -> &Route {
+        &self.route
+    }
+
+    pub fn get_heading(&self) -> f32 {
+        self.heading
+    }
+
+    pub fn get_target(&self) -> Option<Target> {
+        self.target
+    }
+
+    pub fn get_statuses(&self) -> &Statuses {
+        &self.statuses
+    }
+
+    /// turns towards `target_heading` the short way round, no faster than the turn rate
+    pub fn look_towards(&mut self, target_heading: f32) {
+        self.heading = movement::turn_towards(self.heading, target_heading, self.turn_rate * TICK_SECONDS);
+    }
+
+    pub fn is_facing(&self, target_heading: f32) -> bool {
+        movement::angle_difference(self.heading, target_heading).abs() < 0.01
+    }
+
+    pub fn move_forward(&mut self) {
+        let speed = self.speed * self.statuses.get_speed_factor();
+        self.pos = movement::step_along(self.pos, self.heading, speed * TICK_SECONDS);
+    }
+}
+
+/// the lowest numbered "Monster n" none of `monsters` is called, so names aren't repeated
+/// after a retirement
+pub fn get_unused_name(monsters: &[Monster]) -> String {
+    (1..)
+        .map(|number| format!("Monster {}", number))
+        .find(|name| monsters.iter().all(|monster| &monster.name != name))
+        .unwrap()
+}
+
+/// just the head, body and legs of a monster at `pos`, the arms stick out too thin to hit
+pub fn monster_hitbox_at(pos: mint::Point2<f32>) -> graphics::Rect {
+    graphics::Rect::new(pos.x, pos.y, MONSTER_WIDTH, MONSTER_HEIGHT)
+}
+
+/// humans turn about `pos`, so the box is kept around it whichever way they face
+pub fn human_hitbox_at(pos: mint::Point2<f32>) -> graphics::Rect {
+    graphics::Rect::new(pos.x - HUMAN_SIZE / 2.0, pos.y - HUMAN_SIZE / 2.0, HUMAN_SIZE, HUMAN_SIZE)
+}
+
+/// Who fired a projectile, which decides what it can hit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shooter {
+    Human,
+    /// the index of the monster that fired, `None` once it has died
+    Monster(Option<usize>),
+}
+
+/// A bullet or glob of spit in flight. It keeps going in a straight line until it
+/// hits something, is blocked by scenery, or runs out of range.
+pub struct Projectile {
+    pub(crate) shooter: Shooter,
+    pub(crate) pos: mint::Point2<f32>,
+    pub(crate) heading: f32,
+    pub(crate) speed: f32, // pixels per second
+    pub(crate) attack: Attack,
+    pub(crate) range_left: f32, // pixels it can still travel
+    pub(crate) effect: Option<StatusEffect>, // left on whatever it hits
+}
+
+impl Projectile {
     pub fn new(
-        sprite_index: usize,
+        shooter: Shooter,
         pos: mint::Point2<f32>,
+        heading: f32,
         speed: f32,
+        attack: Attack,
         range: f32,
-        hp: f32,
-        damage: f32,
+        effect: Option<StatusEffect>,
     ) -> Self {
         Self {
-            sprite_index,
-            tilt: 0.0,
+            shooter,
             pos,
+            heading,
             speed,
-            range,
-            total_hp: hp,
-            hp,
-            damage,
-            cooldown: 0,
+            attack,
+            range_left: range,
+            effect,
         }
     }
 
-    pub fn look_towards(&mut self, target_tilt: f32) {
-        if target_tilt > self.tilt {
-            self.tilt = target_tilt.min(self.tilt + (PI / 4.0));
-        } else if target_tilt < self.tilt {
-            self.tilt = target_tilt.max(self.tilt - (PI / 4.0));
-        }
+    pub fn get_shooter(&self) -> Shooter {
+        self.shooter
+    }
+
+    pub fn get_pos(&self) -> mint::Point2<f32> {
+        self.pos
+    }
+
+    pub fn get_heading(&self) -> f32 {
+        self.heading
+    }
+}
+
+/// the part of the map a monster can see, ignoring trees
+fn add_view_cone(builder: &mut graphics::MeshBuilder, view: &ViewCone, color: graphics::Color) -> GameResult {
+    const ARC_SEGMENTS: usize = 16;
+    let start = view.heading - view.field_of_view / 2.0;
+    let mut points = vec![view.eye];
+    for segment in 0..=ARC_SEGMENTS {
+        let angle = start + view.field_of_view * segment as f32 / ARC_SEGMENTS as f32;
+        points.push(movement::step_along(view.eye, angle, view.range));
+    }
+    builder.polygon(graphics::DrawMode::fill(), &points, color)?;
+    Ok(())
+}
+
+fn add_route_line(
+    builder: &mut graphics::MeshBuilder,
+    points: &[mint::Point2<f32>],
+    color: graphics::Color,
+) -> GameResult {
+    if points.len() >= 2 {
+        builder.line(points, 1.0, color)?;
+    }
+    Ok(())
+}
+
+const STATUS_ICON_RADIUS: f32 = 3.0;
+
+fn get_status_color(kind: StatusKind) -> graphics::Color {
+    match kind {
+        StatusKind::Burning => graphics::Color::from_rgb(255, 140, 0),
+        StatusKind::Poison => graphics::Color::from_rgb(150, 220, 40),
+        StatusKind::Slow => graphics::Color::from_rgb(100, 160, 255),
+        StatusKind::Stun => graphics::Color::from_rgb(255, 240, 80),
+        StatusKind::Bleed => graphics::Color::from_rgb(200, 0, 0),
+        StatusKind::Regeneration => graphics::Color::from_rgb(80, 255, 160),
     }
+}
 
-    pub fn move_along_tilt(&mut self) {
-        self.pos = mint::Point2 {
-            x: self.pos.x - (self.speed * self.tilt.cos()),
-            y: self.pos.y - (self.speed * self.tilt.sin()),
+/// a row of dots, one per kind of effect, starting at `pos` and going right.
+/// What a unit has for good, like a body's regeneration, isn't worth pointing out
+fn draw_status_icons(ctx: &mut Context, statuses: &Statuses, pos: mint::Point2<f32>) -> GameResult {
+    let mut kinds: Vec<StatusKind> = Vec::new();
+    for effect in statuses.get_effects() {
+        if effect.duration.is_finite() && !kinds.contains(&effect.kind) {
+            kinds.push(effect.kind);
         }
     }
+    if kinds.is_empty() {
+        return Ok(()); // an empty mesh can't be built
+    }
+    let mut builder = graphics::MeshBuilder::new();
+    for (i, &kind) in kinds.iter().enumerate() {
+        let center = mint::Point2 {
+            x: pos.x + STATUS_ICON_RADIUS + i as f32 * STATUS_ICON_RADIUS * 3.0,
+            y: pos.y,
+        };
+        builder.circle(graphics::DrawMode::fill(), center, STATUS_ICON_RADIUS, 0.5, get_status_color(kind));
+    }
+    let mesh = builder.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
 }
 
-fn normalize_x(x: f32) -> f32 {
-    return if x < 0.0 {
-        0.0
-    } else if x > SCREEN_SIZE.0 {
-        SCREEN_SIZE.0
-    } else {
-        x
+const RANK_CHEVRON_WIDTH: f32 = 8.0;
+const RANK_CHEVRON_GAP: f32 = 3.0;
+
+/// a gold chevron for every level above the first, stacked upwards from `pos` at the bottom center
+fn draw_rank_badge(ctx: &mut Context, level: u32, pos: mint::Point2<f32>) -> GameResult {
+    if level <= 1 {
+        return Ok(()); // recruits have nothing to show yet
+    }
+    let mut builder = graphics::MeshBuilder::new();
+    let half_width = RANK_CHEVRON_WIDTH / 2.0;
+    for i in 0..level - 1 {
+        let y = pos.y - i as f32 * RANK_CHEVRON_GAP;
+        let points = [
+            mint::Point2 { x: pos.x - half_width, y },
+            mint::Point2 { x: pos.x, y: y - half_width / 2.0 },
+            mint::Point2 { x: pos.x + half_width, y },
+        ];
+        builder.polyline(graphics::DrawMode::stroke(1.5), &points, graphics::Color::from_rgb(230, 190, 40))?;
     }
+    let mesh = builder.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
 }
 
-fn normalize_y(y: f32) -> f32 {
-    return if y < 0.0 {
-        0.0
-    } else if y > SCREEN_SIZE.1 {
-        SCREEN_SIZE.1
+fn roll<R: Rng>(rng: &mut R, (min, max): (f32, f32)) -> f32 {
+    if min < max {
+        rng.gen_range(min, max)
     } else {
-        y
+        min
+    }
+}
+
+const HIT_MARKER_SECONDS: f32 = 0.8; // how long the damage from a hit floats over the target
+const HIT_MARKER_RISE: f32 = 24.0; // how far it floats up in that time
+const CRIT_PITCH: f32 = 0.75; // critical hits sound heavier
+
+/// What a hit did, floating up off whoever took it.
+struct HitMarker {
+    pos: mint::Point2<f32>, // where it started
+    text: String,
+    color: graphics::Color,
+    scale: f32,
+    age: f32, // seconds
+}
+
+impl HitMarker {
+    fn rank_up(level: u32, pos: mint::Point2<f32>) -> Self {
+        Self {
+            pos,
+            text: format!("{}!", veterancy::get_rank(level)),
+            color: graphics::Color::from_rgb(230, 190, 40),
+            scale: 18.0,
+            age: 0.0,
+        }
+    }
+
+    fn new(hit: &HitResult, pos: mint::Point2<f32>) -> Self {
+        let color = match hit.damage_type {
+            DamageType::Ballistic => graphics::Color::from_rgb(240, 240, 240),
+            DamageType::Slashing => graphics::Color::from_rgb(230, 40, 40),
+            DamageType::Blunt => graphics::Color::from_rgb(220, 180, 120),
+            DamageType::Fire => graphics::Color::from_rgb(255, 150, 0),
+        };
+        let (text, color, scale) = match hit.outcome {
+            HitOutcome::Miss => ("miss".to_string(), graphics::Color::from_rgb(170, 170, 170), 14.0),
+            HitOutcome::Hit => (format!("{:.0}", hit.damage), color, 16.0),
+            HitOutcome::Critical => (format!("{:.0}!", hit.damage), color, 22.0),
+        };
+        Self {
+            pos,
+            text,
+            color,
+            scale,
+            age: 0.0,
+        }
     }
 }
 
+pub const CONTROL_GROUPS: usize = 9;
+const DRAG_THRESHOLD: f32 = 4.0; // a drag smaller than this is just a click
+const FORMATION_SPACING: f32 = MONSTER_WIDTH * 1.25; // between monsters sent somewhere together
+
+/// the box between two corners dragged out in any direction
+fn drag_rect(start: mint::Point2<f32>, end: mint::Point2<f32>) -> graphics::Rect {
+    graphics::Rect::new(start.x.min(end.x), start.y.min(end.y), (end.x - start.x).abs(), (end.y - start.y).abs())
+}
+
 pub struct AttackState {
-    human_sprites: Vec<Image>,
-    monsters: Vec<Monster>,
-    humans: Vec<Human>,
+    human_sprites: Vec<Image>, // one per archetype
+    human_tints: Vec<graphics::Color>,
+    part_sprites: PartSprites,
     gunshot_sound: audio::Source,
     hit_sounds: Vec<audio::Source>,
-    thread_rng: rand::rngs::ThreadRng,
+    sound_rng: Pcg32,
     tree_sprite_batch: graphics::spritebatch::SpriteBatch,
-    time_left: usize,
+    simulation: Simulation,
+    events: Vec<SimEvent>,
+    show_routes: bool, // debug overlay of the pathfinding and what monsters can see
+    selected: Vec<usize>, // monster indices
+    control_groups: Vec<Vec<usize>>, // monster indices, kept for the night
+    drag: Option<(mint::Point2<f32>, mint::Point2<f32>)>, // where a selection box started and is now
+    pending_orders: Vec<(usize, Option<Order>)>, // given since the last tick
+    hit_markers: Vec<HitMarker>,
 }
 
 impl AttackState {
     pub fn new(
-        human_sprites: Vec<Image>,
+        humans: HumanCatalogue,
+        waves: WaveScript,
+        part_sprites: PartSprites,
         gunshot_sound: audio::Source,
         hit_sounds: Vec<audio::Source>,
         tree_sprite: graphics::Image,
+        seed: u64,
     ) -> Self {
-        let tree_sprite_batch = graphics::spritebatch::SpriteBatch::new(tree_sprite.clone());
+        let tree_sprite_batch = graphics::spritebatch::SpriteBatch::new(tree_sprite);
+        let human_tints = humans
+            .archetypes
+            .iter()
+            .map(|archetype| graphics::Color::from_rgb(archetype.tint.0, archetype.tint.1, archetype.tint.2))
+            .collect();
+        let simulation = Simulation::new(humans.archetypes, waves, seed);
         Self {
-            human_sprites,
-            monsters: Vec::new(),
-            humans: Vec::new(),
+            human_sprites: humans.sprites,
+            human_tints,
+            part_sprites,
             gunshot_sound,
             hit_sounds,
-            thread_rng: rand::thread_rng(),
+            sound_rng: Pcg32::seed_from_u64(seed), // kept apart so audio can't change the simulation
             tree_sprite_batch,
-            time_left: FRAMES_TO_WIN_STAGE,
+            simulation,
+            events: Vec::new(),
+            show_routes: false,
+            selected: Vec::new(),
+            control_groups: vec![Vec::new(); CONTROL_GROUPS],
+            drag: None,
+            pending_orders: Vec::new(),
+            hit_markers: Vec::new(),
         }
     }
 
+    pub fn get_simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+
+    pub fn get_simulation_mut(&mut self) -> &mut Simulation {
+        &mut self.simulation
+    }
+
+    pub fn restore(&mut self, seed: u64, rng: Pcg32, monsters: Vec<Monster>) {
+        self.simulation.restore(rng, monsters);
+        self.sound_rng = Pcg32::seed_from_u64(seed);
+        self.tree_sprite_batch.clear();
+    }
+
+    pub fn reset(&mut self, seed: u64) {
+        self.simulation.reset(seed);
+        self.sound_rng = Pcg32::seed_from_u64(seed);
+        self.tree_sprite_batch.clear();
+    }
+
     pub fn reset_monster_pos(&mut self) {
-        for monster in &mut self.monsters {
-            let new_point = mint::Point2 { x: 0.0, y: self.thread_rng.gen_range(0.0, SCREEN_SIZE.1-96.0) };
-            monster.pos = new_point;
+        self.simulation.reset_monster_pos();
+    }
+
+    pub fn toggle_route_overlay(&mut self) {
+        self.show_routes = !self.show_routes;
+    }
+
+    pub fn add_monster(&mut self, head: Head, body: Body, arms: Arms, legs: Legs, behaviour: &'static dyn Behaviour) {
+        self.simulation.add_monster(head, body, arms, legs, behaviour);
+    }
+
+    /// monsters may have come and gone during the day, so selections start afresh
+    pub fn start_night(&mut self, day: u16) {
+        self.simulation.start_night(day);
+        self.selected.clear();
+        for group in &mut self.control_groups {
+            group.clear();
         }
+        self.drag = None;
+        self.pending_orders.clear();
+        self.hit_markers.clear();
     }
 
-    pub fn move_monster_left(&mut self) {
-        // NOTE: for debugging
-        let monster = &mut self.monsters[0];
-        monster.pos = mint::Point2 {
-            x: monster.pos.x - monster.legs.get_speed(),
-            y: monster.pos.y,
-        };
+    pub fn begin_selection(&mut self, pos: mint::Point2<f32>) {
+        self.drag = Some((pos, pos));
     }
 
-    pub fn move_monster_right(&mut self) {
-        // NOTE: for debugging
-        let monster = &mut self.monsters[0];
-        monster.pos = mint::Point2 {
-            x: monster.pos.x + monster.legs.get_speed(),
-            y: monster.pos.y,
-        };
+    pub fn drag_selection(&mut self, pos: mint::Point2<f32>) {
+        if let Some((start, _)) = self.drag {
+            self.drag = Some((start, pos));
+        }
     }
 
-    pub fn move_monster_down(&mut self) {
-        // NOTE: for debugging
-        let monster = &mut self.monsters[0];
-        monster.pos = mint::Point2 {
-            x: monster.pos.x,
-            y: monster.pos.y + monster.legs.get_speed(),
+    /// selects the monster clicked on, or every monster in the dragged box.
+    /// With `add` they are added to the selection instead of replacing it
+    pub fn end_selection(&mut self, pos: mint::Point2<f32>, add: bool) {
+        let start = match self.drag.take() {
+            Some((start, _)) => start,
+            None => return,
+        };
+        let area = drag_rect(start, pos);
+        let monsters = self.simulation.get_monsters();
+        let chosen: Vec<usize> = if area.w < DRAG_THRESHOLD && area.h < DRAG_THRESHOLD {
+            // the last drawn is on top, so that's the one clicked
+            monsters.iter().rposition(|monster| monster.get_hitbox().contains(pos)).into_iter().collect()
+        } else {
+            (0..monsters.len()).filter(|&i| monsters[i].get_hitbox().overlaps(&area)).collect()
         };
+        if !add {
+            self.selected.clear();
+        }
+        for index in chosen {
+            if !self.selected.contains(&index) {
+                self.selected.push(index);
+            }
+        }
     }
 
-    pub fn move_monster_up(&mut self) {
-        // NOTE: for debugging
-        let monster = &mut self.monsters[0];
-        monster.pos = mint::Point2 {
-            x: monster.pos.x,
-            y: monster.pos.y - monster.legs.get_speed(),
-        };
+    /// sends the selected monsters after the human at `pos`, or to `pos` if nobody is there
+    pub fn order_selected(&mut self, pos: mint::Point2<f32>) {
+        let humans = self.simulation.get_humans();
+        if let Some(target) = humans.iter().position(|human| human.get_hitbox().contains(pos)) {
+            for &index in &self.selected {
+                self.pending_orders.push((index, Some(Order::Attack(target))));
+            }
+            return;
+        }
+
+        // spread out in a square around the point, so they aren't all fighting over one spot
+        let columns = (self.selected.len() as f32).sqrt().ceil() as usize;
+        let rows = self.selected.len().div_ceil(columns.max(1));
+        for (k, &index) in self.selected.iter().enumerate() {
+            let destination = mint::Point2 {
+                x: pos.x + ((k % columns) as f32 - (columns - 1) as f32 / 2.0) * FORMATION_SPACING,
+                y: pos.y + ((k / columns) as f32 - (rows - 1) as f32 / 2.0) * FORMATION_SPACING,
+            };
+            self.pending_orders.push((index, Some(Order::Move(destination))));
+        }
     }
 
-    pub fn add_monster(&mut self, head: Head, body: Body, arms: Arms, legs: Legs) {
-        let new_point = mint::Point2 { x: 0.0, y: self.thread_rng.gen_range(0.0, SCREEN_SIZE.1-96.0) };
-        self.monsters.push(Monster::new(
-            head,
-            body,
-            arms,
-            legs,
-            new_point,
-        )); // NOTE: ^monsters are close together^
+    pub fn hold_selected(&mut self) {
+        for &index in &self.selected {
+            self.pending_orders.push((index, Some(Order::Hold)));
+        }
     }
 
-    pub fn generate_humans(&mut self, day: u16) {
-        self.humans.clear();
-        for i in 1..(day+1) {
-            let rnggen = &mut self.thread_rng;
-            let new_pos = mint::Point2 {
-                x: SCREEN_SIZE.0 - 32.0,
-                y: rnggen.gen_range(0.0, SCREEN_SIZE.1-32.0),
-            };
-            self.humans.push(Human::new(
-                rnggen.gen_range(0, self.human_sprites.len()), // index
-                new_pos,
-                rnggen.gen_range(2.0, i as f32*2.1), // speed
-                rnggen.gen_range(50.0, i as f32*50.1), // range
-                rnggen.gen_range(20.0, i as f32*20.1), // hp
-                rnggen.gen_range(10.0, i as f32*10.1), // damage
-            ));
+    /// lets the selected monsters go back to fending for themselves
+    pub fn release_selected(&mut self) {
+        for &index in &self.selected {
+            self.pending_orders.push((index, None));
         }
     }
 
-    pub fn generate_scenery(&mut self) {
-        self.tree_sprite_batch.clear();
+    pub fn assign_control_group(&mut self, group: usize) {
+        self.control_groups[group] = self.selected.clone();
+    }
 
-        let y = 0.0;
-        let mut x = 0.0;
-        while x < SCREEN_SIZE.0 {
-            let point = mint::Point2 { x, y };
-            self.tree_sprite_batch.add((point,));
+    pub fn select_control_group(&mut self, group: usize) {
+        self.selected = self.control_groups[group].clone();
+    }
 
-            x += self.thread_rng.gen_range(50.0, 100.0);
+    /// monster indices shift down when one dies, so the selections have to follow
+    fn forget_monster(&mut self, index: usize) {
+        for indices in std::iter::once(&mut self.selected).chain(self.control_groups.iter_mut()) {
+            indices.retain(|&i| i != index);
+            for i in indices.iter_mut() {
+                if *i > index {
+                    *i -= 1;
+                }
+            }
         }
+    }
 
-        let y = SCREEN_SIZE.1 - 32.0;
-        let mut x = 0.0;
-        while x < SCREEN_SIZE.0 {
-            let point = mint::Point2 { x, y };
-            self.tree_sprite_batch.add((point,));
+    pub fn generate_scenery(&mut self) {
+        self.simulation.generate_scenery();
 
-            x += self.thread_rng.gen_range(50.0, 100.0);
+        self.tree_sprite_batch.clear();
+        for obstacle in self.simulation.get_obstacles() {
+            self.tree_sprite_batch.add((obstacle.point(),));
         }
     }
 
     pub fn draw(&mut self, ctx: &mut Context) -> GameResult {
         self.draw_scenery(ctx)?;
+        self.draw_nest(ctx, self.simulation.get_nest())?;
 
-        for monster in &self.monsters {
+        for monster in self.simulation.get_monsters() {
             self.draw_monster(ctx, monster, 0.5)?;
         }
-        for human in &self.humans {
+        for human in self.simulation.get_humans() {
             self.draw_human(ctx, human)?;
         }
+        self.draw_projectiles(ctx, self.simulation.get_projectiles())?;
+        if self.show_routes {
+            self.draw_routes(ctx)?;
+        }
+        self.draw_selection(ctx)?;
+        self.draw_hit_markers(ctx)?;
+        Ok(())
+    }
+
+    fn draw_hit_markers(&self, ctx: &mut Context) -> GameResult {
+        for marker in &self.hit_markers {
+            let progress = marker.age / HIT_MARKER_SECONDS;
+            let mut color = marker.color;
+            color.a = 1.0 - progress;
+            let text = graphics::Text::new(graphics::TextFragment {
+                text: marker.text.clone(),
+                color: Some(color),
+                font: None,
+                scale: Some(graphics::Scale::uniform(marker.scale)),
+            });
+            let pos = mint::Point2 {
+                x: marker.pos.x - text.width(ctx) as f32 / 2.0,
+                y: marker.pos.y - HIT_MARKER_RISE * progress,
+            };
+            graphics::draw(ctx, &text, (pos,))?;
+        }
+        Ok(())
+    }
+
+    /// outlines the selected monsters with where they've been told to go, and the selection box
+    fn draw_selection(&self, ctx: &mut Context) -> GameResult {
+        if self.selected.is_empty() && self.drag.is_none() {
+            return Ok(());
+        }
+        let monsters = self.simulation.get_monsters();
+        let humans = self.simulation.get_humans();
+        let mut builder = graphics::MeshBuilder::new();
+        for &index in &self.selected {
+            let monster = &monsters[index];
+            let color = match monster.order {
+                Some(Order::Hold) => graphics::Color::from_rgb(240, 200, 40),
+                _ => graphics::Color::from_rgb(60, 220, 60),
+            };
+            builder.rectangle(graphics::DrawMode::stroke(1.5), monster.get_hitbox(), color);
+            match monster.order {
+                Some(Order::Move(destination)) => {
+                    builder.line(&[monster.get_center(), destination], 1.0, graphics::Color::from_rgb(60, 220, 60))?;
+                }
+                Some(Order::Attack(target)) if target < humans.len() => {
+                    builder.line(&[monster.get_center(), humans[target].pos], 1.0, graphics::Color::from_rgb(230, 50, 50))?;
+                }
+                _ => (),
+            }
+        }
+        if let Some((start, end)) = self.drag {
+            let area = drag_rect(start, end);
+            if area.w >= DRAG_THRESHOLD || area.h >= DRAG_THRESHOLD {
+                builder.rectangle(graphics::DrawMode::stroke(1.0), area, graphics::WHITE);
+            }
+        }
+        // a click with nothing selected leaves nothing to draw
+        if let Ok(mesh) = builder.build(ctx) {
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+        }
+        Ok(())
+    }
+
+    /// the tiles humans can't stand on, what each monster can see and the way every unit means to go
+    fn draw_routes(&self, ctx: &mut Context) -> GameResult {
+        let mut builder = graphics::MeshBuilder::new();
+        for tile in self.simulation.get_human_nav().get_blocked_tiles() {
+            builder.rectangle(graphics::DrawMode::fill(), tile, graphics::Color::new(1.0, 0.0, 0.0, 0.15));
+        }
+        for monster in self.simulation.get_monsters() {
+            add_view_cone(&mut builder, &monster.get_view_cone(), graphics::Color::new(1.0, 1.0, 0.4, 0.1))?;
+        }
+        // monster routes are for their top-left corner, so draw them through the middle
+        let monster_offset = mint::Vector2 { x: MONSTER_WIDTH / 2.0, y: MONSTER_HEIGHT / 2.0 };
+        for monster in self.simulation.get_monsters() {
+            let points: Vec<_> = std::iter::once(&monster.pos)
+                .chain(monster.route.get_waypoints())
+                .map(|point| mint::Point2 { x: point.x + monster_offset.x, y: point.y + monster_offset.y })
+                .collect();
+            add_route_line(&mut builder, &points, graphics::Color::from_rgb(60, 200, 60))?;
+        }
+        for human in self.simulation.get_humans() {
+            let points: Vec<_> = std::iter::once(&human.pos).chain(human.route.get_waypoints()).copied().collect();
+            add_route_line(&mut builder, &points, graphics::Color::from_rgb(220, 60, 60))?;
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+
         Ok(())
     }
 
@@ -251,8 +984,29 @@ impl AttackState {
         Ok(())
     }
 
+    fn draw_nest(&self, ctx: &mut Context, nest: &Nest) -> GameResult {
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            nest.get_center(),
+            NEST_SIZE / 2.0,
+            1.0,
+            graphics::Color::from_rgb(110, 75, 40),
+        )?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+
+        let health_height = 10;
+        let health_rect = graphics::Image::solid(ctx, health_height, graphics::Color::from_rgb(0, 255, 0))?;
+        let actual_length = (NEST_SIZE*(nest.hp.max(0.0)/nest.total_hp)) as u16;
+        let x_scaling = actual_length as f32/health_height as f32; // health_height is also width
+        let pos = mint::Point2 { x: nest.pos.x, y: nest.pos.y+NEST_SIZE+4.0 };
+        graphics::draw(ctx, &health_rect, graphics::DrawParam::from((pos,)).scale([x_scaling, 1.0]))?;
+
+        Ok(())
+    }
+
     fn draw_monster(&self, ctx: &mut Context, monster: &Monster, scaling: f32) -> GameResult {
-        let body_part_side_length = monster.head.get_image().width() as f32 * scaling;
+        let body_part_side_length = monster.head.get_image(&self.part_sprites).width() as f32 * scaling;
         let scale_vector = mint::Point2 {
             x: scaling,
             y: scaling,
@@ -260,12 +1014,12 @@ impl AttackState {
 
         graphics::draw(
             ctx,
-            monster.head.get_image(),
+            monster.head.get_image(&self.part_sprites),
             graphics::DrawParam::from((monster.pos,)).scale(scale_vector),
         )?;
         graphics::draw(
             ctx,
-            monster.body.get_image(),
+            monster.body.get_image(&self.part_sprites),
             graphics::DrawParam::from((mint::Point2 {
                 x: monster.pos.x,
                 y: monster.pos.y + body_part_side_length,
@@ -274,7 +1028,7 @@ impl AttackState {
         )?;
         graphics::draw(
             ctx,
-            monster.arms.get_image(),
+            monster.arms.get_image(&self.part_sprites),
             graphics::DrawParam::from((mint::Point2 {
                 x: monster.pos.x - body_part_side_length,
                 y: monster.pos.y + body_part_side_length,
@@ -283,7 +1037,7 @@ impl AttackState {
         )?;
         graphics::draw(
             ctx,
-            monster.arms.get_image(),
+            monster.arms.get_image(&self.part_sprites),
             graphics::DrawParam::from((mint::Point2 {
                 x: monster.pos.x + (body_part_side_length * 2.0),
                 y: monster.pos.y + body_part_side_length,
@@ -295,7 +1049,7 @@ impl AttackState {
         )?;
         graphics::draw(
             ctx,
-            monster.legs.get_image(),
+            monster.legs.get_image(&self.part_sprites),
             graphics::DrawParam::from((mint::Point2 {
                 x: monster.pos.x,
                 y: monster.pos.y + (body_part_side_length * 2.0),
@@ -305,18 +1059,31 @@ impl AttackState {
 
         let health_height = 10;
         let health_rect = graphics::Image::solid(ctx, health_height, graphics::Color::from_rgb(0, 255, 0))?;
-        let actual_length = (32.0*(monster.hp/monster.body.get_health())) as u16;
+        let actual_length = (32.0*(monster.hp/monster.get_total_hp())) as u16;
         let x_scaling = actual_length as f32/health_height as f32; // health_height is also width
         let pos = mint::Point2 { x: monster.pos.x, y: monster.pos.y+96.0 };
         graphics::draw(ctx, &health_rect, graphics::DrawParam::from((pos,)).scale([x_scaling, 1.0]))?;
 
+        let icons_pos = mint::Point2 { x: monster.pos.x, y: monster.pos.y - STATUS_ICON_RADIUS * 2.0 };
+        draw_status_icons(ctx, &monster.statuses, icons_pos)?;
+        let badge_pos = mint::Point2 {
+            x: monster.pos.x + body_part_side_length / 2.0,
+            y: icons_pos.y - STATUS_ICON_RADIUS * 2.0,
+        };
+        draw_rank_badge(ctx, monster.get_level(), badge_pos)?;
+
         Ok(())
     }
 
     fn draw_human(&self, ctx: &mut Context, human: &Human) -> GameResult {
-        let human_sprite = &self.human_sprites[human.sprite_index];
-        let mut params = graphics::DrawParam::from((human.pos,)).rotation(human.tilt);
-        if human.tilt.abs() > PI / 2.0 {
+        let human_sprite = &self.human_sprites[human.archetype];
+        // the sprites face left, so turn them half way round from the heading
+        let rotation = movement::wrap_angle(human.heading - PI);
+        let mut params = graphics::DrawParam::from((human.pos,))
+            .rotation(rotation)
+            .color(self.human_tints[human.archetype]);
+        if rotation.abs() > PI / 2.0 {
+            // keep them upright when facing right
             params = params.scale([1.0, -1.0])
         }
         if human.hp < human.total_hp*0.75 { // change color of human depending on health
@@ -326,200 +1093,110 @@ impl AttackState {
 
         graphics::draw(ctx, human_sprite, params)?;
 
+        let icons_pos = mint::Point2 {
+            x: human.pos.x - HUMAN_SIZE / 2.0,
+            y: human.pos.y - HUMAN_SIZE / 2.0 - STATUS_ICON_RADIUS * 2.0,
+        };
+        draw_status_icons(ctx, &human.statuses, icons_pos)?;
+
         Ok(())
     }
 
-    /// optionally returns if true if monster won, else false
-    pub fn update_state(&mut self, ctx: &mut Context) -> Option<bool> {
-        self.time_left -= 1;
+    fn draw_projectiles(&self, ctx: &mut Context, projectiles: &[Projectile]) -> GameResult {
+        if projectiles.is_empty() {
+            return Ok(()); // an empty mesh can't be built
+        }
+        let mut builder = graphics::MeshBuilder::new();
+        for projectile in projectiles {
+            match projectile.shooter {
+                Shooter::Human => {
+                    // a short streak trailing behind the bullet
+                    let tail = movement::step_along(projectile.pos, projectile.heading, -6.0);
+                    builder.line(&[tail, projectile.pos], 2.0, graphics::Color::from_rgb(255, 230, 120))?;
+                }
+                Shooter::Monster(_) => {
+                    builder.circle(
+                        graphics::DrawMode::fill(),
+                        projectile.pos,
+                        4.0,
+                        0.5,
+                        graphics::Color::from_rgb(120, 200, 60),
+                    );
+                }
+            }
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
 
-        self.update_humans();
-        self.update_monsters(ctx);
+        Ok(())
+    }
 
-        let out;
-        if self.monsters.is_empty() || self.humans.is_empty() || self.time_left == 0 {
-            out = Some(self.humans.is_empty() || self.time_left == 0);
-            self.time_left = FRAMES_TO_WIN_STAGE;
-        } else {
-            out = None;
+    /// optionally returns if true if monster won, else false
+    pub fn update_state(&mut self) -> Option<bool> {
+        let input = SimInput {
+            orders: std::mem::take(&mut self.pending_orders),
         };
-        return out;
-    }
 
-    fn update_humans(&mut self) {
-        for i in 0..self.humans.len() {
-            if self.monsters.is_empty() {
-                break;
-            }
-            let (target_index, distance) = self.get_closest_monster(&self.humans[i].pos);
-            let target = &mut self.monsters[target_index];
-            let acute_tilt = get_acute_tilt(&target.pos, &self.humans[i].pos);
-            // HACK: acute_tilt is correct, but actual_tilt is rather unintuitive:
-            let actual_tilt = if target.pos.x >= self.humans[i].pos.x
-                && target.pos.y <= self.humans[i].pos.y
-            {
-                PI / 2.0 + ((PI / 2.0) - acute_tilt) //PI+acute_tilt
-            } else if target.pos.x >= self.humans[i].pos.x {
-                -((PI / 2.0) + ((PI / 2.0) - acute_tilt))
-            } else if target.pos.x < self.humans[i].pos.x && target.pos.y < self.humans[i].pos.y {
-                acute_tilt
-            } else {
-                -acute_tilt //PI+(PI/2.0)+((PI/2.0)-acute_tilt)
-            };
+        self.events.clear();
+        let out = self.simulation.step(&input, &mut self.events);
+        let died: Vec<usize> = self
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                SimEvent::MonsterDied { monster } => Some(*monster),
+                _ => None,
+            })
+            .collect();
+        for monster in died {
+            self.forget_monster(monster);
+        }
+        self.update_hit_markers();
+        self.play_event_sounds();
+        out
+    }
 
-            self.humans[i].look_towards(actual_tilt); // update tilt
-
-            if distance >= self.humans[i].range {
-                // move if not in range
-                self.humans[i].move_along_tilt();
-            } else if (self.humans[i].tilt - actual_tilt).abs() < 0.01
-                && self.humans[i].cooldown == 0
-            {
-                if let Err(error) = self.gunshot_sound.play() {
-                    // play gunshot
-                    eprintln!("{}", error);
+    /// ages the markers already up and adds one for every hit this tick
+    fn update_hit_markers(&mut self) {
+        for marker in &mut self.hit_markers {
+            marker.age += TICK_SECONDS;
+        }
+        self.hit_markers.retain(|marker| marker.age < HIT_MARKER_SECONDS);
+        let nest_center = self.simulation.get_nest().get_center();
+        for event in &self.events {
+            let (hit, at) = match event {
+                SimEvent::MonsterShot { hit, at, .. } | SimEvent::MonsterHit { hit, at, .. } | SimEvent::HumanHit { hit, at, .. } => {
+                    (hit, *at)
                 }
-                target.hp -= self.humans[i].damage;
-                if target.hp <= 0.0 {
-                    self.monsters.remove(target_index);
+                SimEvent::NestShot { hit } => (hit, nest_center),
+                SimEvent::MonsterRankedUp { level, at, .. } => {
+                    self.hit_markers.push(HitMarker::rank_up(*level, *at));
+                    continue;
                 }
-                self.humans[i].cooldown = 75;
-            }
-            if self.humans[i].cooldown > 0 {
-                self.humans[i].cooldown -= 1;
-            }
+                _ => continue,
+            };
+            self.hit_markers.push(HitMarker::new(hit, at));
         }
     }
 
-    fn update_monsters(&mut self, ctx: &mut Context) {
-        for i in 0..self.monsters.len() {
-            if self.humans.is_empty() {
-                break;
-            }
-            // center_point assumes half scaling
-            let monster_center_point = mint::Point2 {
-                x: self.monsters[i].pos.x + 16.0,
-                y: self.monsters[i].pos.y + 48.0,
-            };
-            let (possible_target_index, distance) = self.get_closest_human(&monster_center_point);
-            let possible_target = &mut self.humans[possible_target_index];
-            let sight_range = self.monsters[i].head.get_sight_range();
-            if distance <= sight_range / 2.0 {
-                // NOTE: attack range is 1/4 of sight
-                if self.monsters[i].cooldown == 0 {
-                    let choice = self.thread_rng.gen_range(0, self.hit_sounds.len());
-                    if let Err(error) = self.hit_sounds[choice].play() {
+    fn play_event_sounds(&mut self) {
+        for event in &self.events {
+            match event {
+                SimEvent::HumanFired { .. } => {
+                    if let Err(error) = self.gunshot_sound.play() {
                         // play gunshot
                         eprintln!("{}", error);
                     }
-
-                    possible_target.hp -= self.monsters[i].arms.get_damage();
-                    if possible_target.hp <= 0.0 {
-                        self.humans.remove(possible_target_index);
+                }
+                SimEvent::MonsterHit { hit, .. } | SimEvent::HumanHit { hit, .. } if !hit.is_miss() => {
+                    let choice = self.sound_rng.gen_range(0, self.hit_sounds.len());
+                    let sound = &mut self.hit_sounds[choice];
+                    sound.set_pitch(if hit.outcome == HitOutcome::Critical { CRIT_PITCH } else { 1.0 });
+                    if let Err(error) = sound.play() {
+                        eprintln!("{}", error);
                     }
-                    self.monsters[i].cooldown = 40;
                 }
-            } else if distance <= sight_range {
-                let acute_angle = get_acute_tilt(&possible_target.pos, &self.monsters[i].pos);
-                let (curr_x, curr_y) = (self.monsters[i].pos.x, self.monsters[i].pos.y);
-                let speed = self.monsters[i].legs.get_speed();
-
-                self.monsters[i].pos = mint::Point2 {
-                    x: curr_x
-                        + if curr_x <= possible_target.pos.x {
-                            speed * acute_angle.cos()
-                        } else {
-                            -speed * acute_angle.cos()
-                        },
-                    y: curr_y
-                        + if curr_y <= possible_target.pos.y {
-                            speed * acute_angle.sin()
-                        } else {
-                            -speed * acute_angle.sin()
-                        },
-                };
-            } else if input::mouse::button_pressed(ctx, input::mouse::MouseButton::Left) {
-                let curr_mouse_pos = input::mouse::position(ctx);
-                let acute_angle = get_acute_tilt(&curr_mouse_pos, &self.monsters[i].pos);
-                let (curr_x, curr_y) = (self.monsters[i].pos.x, self.monsters[i].pos.y);
-                let speed = self.monsters[i].legs.get_speed();
-
-                let new_x = curr_x + if curr_x <= curr_mouse_pos.x {
-                    speed * acute_angle.cos()
-                } else {
-                    -speed * acute_angle.cos()
-                };
-                let new_y = curr_y + if curr_y <= curr_mouse_pos.y {
-                    speed * acute_angle.sin()
-                } else {
-                    -speed * acute_angle.sin()
-                };
-
-                self.monsters[i].pos = mint::Point2 {
-                    x: normalize_x(new_x),
-                    y: normalize_y(new_y),
-                };
-            } else {
-                let choice: i8 = self.thread_rng.gen_range(0, 4);
-                let speed = self.monsters[i].legs.get_speed() / 4.0;
-                let new_pos = match choice {
-                    0 => mint::Point2 {
-                        x: self.monsters[i].pos.x,
-                        y: 0.0f32.max(self.monsters[i].pos.y - speed),
-                    },
-                    1 => mint::Point2 {
-                        x: SCREEN_SIZE.0.min(self.monsters[i].pos.x + speed),
-                        y: self.monsters[i].pos.y,
-                    },
-                    2 => mint::Point2 {
-                        x: self.monsters[i].pos.x,
-                        y: SCREEN_SIZE.1.min(self.monsters[i].pos.y + speed),
-                    },
-                    3 => mint::Point2 {
-                        x: 0.0f32.max(self.monsters[i].pos.x - speed),
-                        y: self.monsters[i].pos.y,
-                    },
-                    _ => panic!(),
-                };
-                self.monsters[i].pos = new_pos;
-            }
-            if self.monsters[i].cooldown > 0 {
-                self.monsters[i].cooldown -= 1;
-            }
-        }
-    }
-
-    fn get_closest_monster(&self, human_pos: &mint::Point2<f32>) -> (usize, f32) {
-        let mut curr_monster_index = 0;
-        let mut curr_min = f32::INFINITY;
-        for (i, monster) in self.monsters.iter().enumerate() {
-            let temp = get_euclid_distance(human_pos, &monster.pos);
-            if temp < curr_min {
-                curr_min = temp;
-                curr_monster_index = i;
-            }
-        }
-        (curr_monster_index, curr_min)
-    }
-
-    fn get_closest_human(&self, monster_pos: &mint::Point2<f32>) -> (usize, f32) {
-        let mut curr_human_index = 0;
-        let mut curr_min = f32::INFINITY;
-        for (i, human) in self.humans.iter().enumerate() {
-            let temp = get_euclid_distance(monster_pos, &human.pos);
-            if temp < curr_min {
-                curr_min = temp;
-                curr_human_index = i;
+                _ => (),
             }
         }
-        (curr_human_index, curr_min)
     }
 }
-
-fn get_acute_tilt(point1: &mint::Point2<f32>, point2: &mint::Point2<f32>) -> f32 {
-    ((point1.y - point2.y).abs() / (point1.x - point2.x).abs()).atan()
-}
-
-fn get_euclid_distance(point1: &mint::Point2<f32>, point2: &mint::Point2<f32>) -> f32 {
-    (point1.x - point2.x).hypot(point1.y - point2.y)
-}
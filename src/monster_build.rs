@@ -1,109 +1,442 @@
+use crate::ability::{ArmsAbility, BodyAbility, HeadAbility, LegsAbility};
+use crate::behaviour::{Behaviour, BEHAVIOURS};
+use crate::combat::{Attack, Resistances};
+use crate::economy::Resources;
+use crate::journal::Journal;
+use crate::status::StatusEffect;
+use crate::tech_tree::TechTree;
 use crate::SCREEN_SIZE;
 use ggez::graphics;
 use ggez::{Context, GameResult};
+use serde::Deserialize;
+
+/// The loaded images for every part, indexed by each part's `sprite_index`.
+/// Kept apart from the parts themselves so monsters can be simulated without a `Context`.
+#[derive(Debug, Clone)]
+pub struct PartSprites {
+    pub heads: Vec<graphics::Image>,
+    pub bodies: Vec<graphics::Image>,
+    pub arms: Vec<graphics::Image>,
+    pub legs: Vec<graphics::Image>,
+}
 
 pub trait Sprite {
-    fn get_image(&self) -> &graphics::Image;
+    fn get_image<'a>(&self, sprites: &'a PartSprites) -> &'a graphics::Image;
+}
+
+/// What the builder needs of any kind of part.
+pub trait Part: Sprite {
+    fn get_id(&self) -> &str;
+    fn get_name(&self) -> &str;
+    /// what building a monster with it takes
+    fn get_cost(&self) -> Resources;
 }
 
 #[derive(Debug, Clone)]
 pub struct Head {
-    sprite: graphics::Image,
+    id: String,
+    name: String,
+    sprite_index: usize,
     sight_range: f32,
+    field_of_view: f32, // radians across the whole cone
+    abilities: Vec<HeadAbility>,
+    cost: Resources,
 }
 
 impl Head {
-    pub fn new(sprite: graphics::Image, sight_range: f32) -> Self {
+    pub fn new(id: String, name: String, sprite_index: usize, sight_range: f32, field_of_view: f32) -> Self {
         Self {
-            sprite,
+            id,
+            name,
+            sprite_index,
             sight_range,
+            field_of_view,
+            abilities: Vec::new(),
+            cost: Resources::default(),
         }
     }
 
+    pub fn with_abilities(mut self, abilities: Vec<HeadAbility>) -> Self {
+        self.abilities = abilities;
+        self
+    }
+
+    pub fn with_cost(mut self, cost: Resources) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
     pub fn get_sight_range(&self) -> f32 {
         self.sight_range
     }
+
+    pub fn get_field_of_view(&self) -> f32 {
+        self.field_of_view
+    }
+
+    pub fn get_abilities(&self) -> &[HeadAbility] {
+        &self.abilities
+    }
+
+    pub fn has_x_ray(&self) -> bool {
+        self.abilities.contains(&HeadAbility::XRay)
+    }
+}
+
+impl Part for Head {
+    fn get_id(&self) -> &str {
+        Head::get_id(self)
+    }
+
+    fn get_name(&self) -> &str {
+        Head::get_name(self)
+    }
+
+    fn get_cost(&self) -> Resources {
+        self.cost
+    }
 }
 
 impl Sprite for Head {
-    fn get_image(&self) -> &graphics::Image {
-        &self.sprite
+    fn get_image<'a>(&self, sprites: &'a PartSprites) -> &'a graphics::Image {
+        &sprites.heads[self.sprite_index]
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Body {
-    sprite: graphics::Image,
+    id: String,
+    name: String,
+    sprite_index: usize,
     health: f32,
+    regeneration: f32, // hp healed a second at night
+    recovery: f32,     // share of its health healed at dawn
+    resistances: Resistances,
+    abilities: Vec<BodyAbility>,
+    cost: Resources,
 }
 
 impl Body {
-    pub fn new(sprite: graphics::Image, health: f32) -> Self {
-        Self { sprite, health }
+    pub fn new(
+        id: String,
+        name: String,
+        sprite_index: usize,
+        health: f32,
+        regeneration: f32,
+        resistances: Resistances,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            sprite_index,
+            health,
+            regeneration,
+            recovery: 0.0,
+            resistances,
+            abilities: Vec::new(),
+            cost: Resources::default(),
+        }
+    }
+
+    pub fn with_abilities(mut self, abilities: Vec<BodyAbility>) -> Self {
+        self.abilities = abilities;
+        self
+    }
+
+    pub fn with_recovery(mut self, recovery: f32) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    pub fn with_cost(mut self, cost: Resources) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 
     pub fn get_health(&self) -> f32 {
         self.health
     }
+
+    pub fn get_regeneration(&self) -> f32 {
+        self.regeneration
+    }
+
+    pub fn get_recovery(&self) -> f32 {
+        self.recovery
+    }
+
+    pub fn get_resistances(&self) -> Resistances {
+        self.resistances
+    }
+
+    pub fn get_abilities(&self) -> &[BodyAbility] {
+        &self.abilities
+    }
+
+    /// how much less damage every shot does
+    pub fn get_armour(&self) -> f32 {
+        self.abilities
+            .iter()
+            .map(|ability| match ability {
+                BodyAbility::Armour(armour) => *armour,
+            })
+            .sum()
+    }
+}
+
+impl Part for Body {
+    fn get_id(&self) -> &str {
+        Body::get_id(self)
+    }
+
+    fn get_name(&self) -> &str {
+        Body::get_name(self)
+    }
+
+    fn get_cost(&self) -> Resources {
+        self.cost
+    }
 }
 
 impl Sprite for Body {
-    fn get_image(&self) -> &graphics::Image {
-        &self.sprite
+    fn get_image<'a>(&self, sprites: &'a PartSprites) -> &'a graphics::Image {
+        &sprites.bodies[self.sprite_index]
     }
 }
 
+/// Lets arms attack from a distance by firing projectiles, e.g. spit.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RangedAttack {
+    /// pixels per second
+    pub speed: f32,
+    /// how close a human has to be before the monster fires
+    pub range: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Arms {
-    sprite: graphics::Image,
-    damage: f32,
+    id: String,
+    name: String,
+    sprite_index: usize,
+    attack: Attack,
+    reach: f32, // how close a human has to be to be hit in melee
+    ranged: Option<RangedAttack>, // melee only if None
+    on_hit: Option<StatusEffect>, // left on whoever these hit
+    abilities: Vec<ArmsAbility>,
+    cost: Resources,
 }
 
 impl Arms {
-    pub fn new(sprite: graphics::Image, damage: f32) -> Self {
-        Self { sprite, damage }
+    pub fn new(
+        id: String,
+        name: String,
+        sprite_index: usize,
+        attack: Attack,
+        reach: f32,
+        ranged: Option<RangedAttack>,
+        on_hit: Option<StatusEffect>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            sprite_index,
+            attack,
+            reach,
+            ranged,
+            on_hit,
+            abilities: Vec::new(),
+            cost: Resources::default(),
+        }
+    }
+
+    pub fn with_abilities(mut self, abilities: Vec<ArmsAbility>) -> Self {
+        self.abilities = abilities;
+        self
+    }
+
+    pub fn with_cost(mut self, cost: Resources) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 
     pub fn get_damage(&self) -> f32 {
-        self.damage
+        self.attack.damage
+    }
+
+    pub fn get_attack(&self) -> Attack {
+        self.attack
+    }
+
+    pub fn get_reach(&self) -> f32 {
+        self.reach
+    }
+
+    /// how close a human has to be before these arms attack, by spit or by hand
+    pub fn get_attack_range(&self) -> f32 {
+        self.ranged.map_or(self.reach, |ranged| ranged.range)
+    }
+
+    pub fn get_ranged(&self) -> Option<RangedAttack> {
+        self.ranged
+    }
+
+    pub fn get_on_hit(&self) -> Option<StatusEffect> {
+        self.on_hit
+    }
+
+    pub fn get_abilities(&self) -> &[ArmsAbility] {
+        &self.abilities
+    }
+
+    /// how close to the target other humans have to be to get hit too, if at all
+    pub fn get_cleave(&self) -> Option<f32> {
+        self.abilities.iter().find_map(|ability| match ability {
+            ArmsAbility::Cleave(radius) => Some(*radius),
+            _ => None,
+        })
+    }
+
+    /// how far a hit shoves a human back, if at all
+    pub fn get_knockback(&self) -> Option<f32> {
+        self.abilities.iter().find_map(|ability| match ability {
+            ArmsAbility::Knockback(distance) => Some(*distance),
+            _ => None,
+        })
+    }
+}
+
+impl Part for Arms {
+    fn get_id(&self) -> &str {
+        Arms::get_id(self)
+    }
+
+    fn get_name(&self) -> &str {
+        Arms::get_name(self)
+    }
+
+    fn get_cost(&self) -> Resources {
+        self.cost
     }
 }
 
 impl Sprite for Arms {
-    fn get_image(&self) -> &graphics::Image {
-        &self.sprite
+    fn get_image<'a>(&self, sprites: &'a PartSprites) -> &'a graphics::Image {
+        &sprites.arms[self.sprite_index]
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Legs {
-    sprite: graphics::Image,
+    id: String,
+    name: String,
+    sprite_index: usize,
     speed: f32,
+    evasion: f32, // chance of a hit missing, from 0 to 1
+    abilities: Vec<LegsAbility>,
+    cost: Resources,
 }
 
 impl Legs {
-    pub fn new(sprite: graphics::Image, speed: f32) -> Self {
-        Self { sprite, speed }
+    pub fn new(id: String, name: String, sprite_index: usize, speed: f32, evasion: f32) -> Self {
+        Self {
+            id,
+            name,
+            sprite_index,
+            speed,
+            evasion,
+            abilities: Vec::new(),
+            cost: Resources::default(),
+        }
+    }
+
+    pub fn with_abilities(mut self, abilities: Vec<LegsAbility>) -> Self {
+        self.abilities = abilities;
+        self
+    }
+
+    pub fn with_cost(mut self, cost: Resources) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 
     pub fn get_speed(&self) -> f32 {
         self.speed
     }
+
+    pub fn get_evasion(&self) -> f32 {
+        self.evasion
+    }
+
+    pub fn get_abilities(&self) -> &[LegsAbility] {
+        &self.abilities
+    }
+
+    pub fn can_jump(&self) -> bool {
+        self.abilities.contains(&LegsAbility::Jump)
+    }
+}
+
+impl Part for Legs {
+    fn get_id(&self) -> &str {
+        Legs::get_id(self)
+    }
+
+    fn get_name(&self) -> &str {
+        Legs::get_name(self)
+    }
+
+    fn get_cost(&self) -> Resources {
+        self.cost
+    }
 }
 
 impl Sprite for Legs {
-    fn get_image(&self) -> &graphics::Image {
-        &self.sprite
+    fn get_image<'a>(&self, sprites: &'a PartSprites) -> &'a graphics::Image {
+        &sprites.legs[self.sprite_index]
     }
 }
 
 pub struct BuilderState {
-    curr_choices: [Option<usize>; 4], // choice for head, body, arms, and legs
+    curr_choices: [Option<usize>; 5], // choice for head, body, arms, legs and behaviour
     possible_heads: Vec<Head>,
     possible_bodies: Vec<Body>,
     possible_arms: Vec<Arms>,
     possible_legs: Vec<Legs>,
-    curr_hover: usize, // index of option to highlight
+    sprites: PartSprites,
+    tech_tree: TechTree, // which of the parts have to be unlocked first
+    curr_hover: usize,   // index of option to highlight
 }
 
 impl BuilderState {
@@ -112,50 +445,67 @@ impl BuilderState {
         possible_bodies: Vec<Body>,
         possible_arms: Vec<Arms>,
         possible_legs: Vec<Legs>,
+        sprites: PartSprites,
+        tech_tree: TechTree,
     ) -> Self {
         BuilderState {
-            curr_choices: [None, None, None, None],
+            curr_choices: [None, None, None, None, None],
             possible_heads,
             possible_bodies,
             possible_arms,
             possible_legs,
+            sprites,
+            tech_tree,
             curr_hover: 0,
         }
     }
 
-    pub fn draw(&self, ctx: &mut Context, font: graphics::Font) -> GameResult {
+    /// `journal` is what the player has learnt about the parts, shown for the hovered one,
+    /// along with how far they've got towards unlocking it, and `resources` what they have
+    /// left to build with
+    pub fn draw(&self, ctx: &mut Context, font: graphics::Font, journal: &Journal, resources: Resources) -> GameResult {
         let head_point = mint::Point2 {
             x: (SCREEN_SIZE.0 / 2.0),
             y: (10.0),
         };
         let text;
+        let (part_name, estimate, abilities);
+        let mut cost = None; // of the hovered part
         if self.curr_choices[0].is_none() {
-            self.draw_options(&self.possible_heads, ctx)?;
+            self.draw_options(&self.possible_heads, ctx, journal, resources)?;
 
-            let head = &self.possible_heads[self.curr_hover];
-            graphics::draw(ctx, head.get_image(), (head_point,))?;
+            let head = &self.possible_heads[self.get_hover()];
+            graphics::draw(ctx, head.get_image(&self.sprites), (head_point,))?;
 
             text = "Choose head:";
+            part_name = head.get_name();
+            estimate = journal.describe_head(head.get_id());
+            abilities = journal.describe_abilities(head.get_id(), head.get_abilities());
+            cost = Some(head.get_cost());
         } else if self.curr_choices[1].is_none() {
-            self.draw_options(&self.possible_bodies, ctx)?;
+            self.draw_options(&self.possible_bodies, ctx, journal, resources)?;
 
             let body_point = mint::Point2 {
                 x: head_point.x,
                 y: (head_point.y + 64.0),
             };
-            let body = &self.possible_bodies[self.curr_hover];
-            graphics::draw(ctx, body.get_image(), (body_point,))?;
+            let body = &self.possible_bodies[self.get_hover()];
+            graphics::draw(ctx, body.get_image(&self.sprites), (body_point,))?;
 
             text = "Choose body:";
+            part_name = body.get_name();
+            estimate = journal.describe_body(body.get_id());
+            abilities = journal.describe_abilities(body.get_id(), body.get_abilities());
+            cost = Some(body.get_cost());
         } else if self.curr_choices[2].is_none() {
-            self.draw_options(&self.possible_arms, ctx)?;
+            self.draw_options(&self.possible_arms, ctx, journal, resources)?;
 
             let arm_point = mint::Point2 {
                 x: head_point.x - 64.0,
                 y: head_point.y + 64.0,
             };
-            let arm = &self.possible_arms[self.curr_hover];
-            graphics::draw(ctx, arm.get_image(), (arm_point,))?;
+            let arm = &self.possible_arms[self.get_hover()];
+            graphics::draw(ctx, arm.get_image(&self.sprites), (arm_point,))?;
 
             let other_arm_point = mint::Point2 {
                 x: head_point.x + 128.0,
@@ -163,26 +513,89 @@ impl BuilderState {
             };
             graphics::draw(
                 ctx,
-                arm.get_image(),
+                arm.get_image(&self.sprites),
                 graphics::DrawParam::from((other_arm_point,)).scale([-1.0, 1.0]),
             )?;
 
             text = "Choose arm";
-        } else {
-            self.draw_options(&self.possible_legs, ctx)?;
+            part_name = arm.get_name();
+            estimate = journal.describe_arms(arm.get_id());
+            abilities = journal.describe_abilities(arm.get_id(), arm.get_abilities());
+            cost = Some(arm.get_cost());
+        } else if self.curr_choices[3].is_none() {
+            self.draw_options(&self.possible_legs, ctx, journal, resources)?;
 
             let leg_point = mint::Point2 {
                 x: head_point.x,
                 y: head_point.y + (64.0 * 2.0),
             };
-            let leg = &self.possible_legs[self.curr_hover];
-            graphics::draw(ctx, leg.get_image(), (leg_point,))?;
+            let leg = &self.possible_legs[self.get_hover()];
+            graphics::draw(ctx, leg.get_image(&self.sprites), (leg_point,))?;
 
             text = "Choose leg";
+            part_name = leg.get_name();
+            estimate = journal.describe_legs(leg.get_id());
+            abilities = journal.describe_abilities(leg.get_id(), leg.get_abilities());
+            cost = Some(leg.get_cost());
+        } else {
+            self.draw_behaviour_options(ctx, font)?;
+
+            let behaviour = BEHAVIOURS[self.get_hover()];
+            text = "Choose how it behaves";
+            part_name = behaviour.get_name();
+            estimate = Some(behaviour.get_description().to_string());
+            abilities = None;
         }
 
         self.draw_choices(ctx)?;
 
+        let mut journal_text = format!(
+            "{}\n{}",
+            part_name,
+            estimate.unwrap_or_else(|| "Not seen enough of this part at night to tell its stats.".to_string())
+        );
+        if let Some(abilities) = abilities {
+            journal_text = format!("{}\n{}", journal_text, abilities);
+        }
+        if let Some(part) = self.get_hovered_part() {
+            let progress = journal.get_progress();
+            let unmet = self.tech_tree.get_unmet(part.get_id(), progress);
+            if !unmet.is_empty() {
+                let get_name = |id: &str| self.get_part_name(id);
+                let conditions: Vec<String> = unmet
+                    .iter()
+                    .map(|condition| format!("- {}", condition.describe(progress, &get_name)))
+                    .collect();
+                journal_text = format!("{}\nLocked, to unlock:\n{}", journal_text, conditions.join("\n"));
+            }
+        }
+        // grows upwards so the last line stays clear of the prompt below
+        let lines = journal_text.lines().count() as f32;
+        let journal_pos = mint::Point2 { x: 5.0, y: SCREEN_SIZE.1-114.0-18.0*lines };
+        graphics::draw(ctx,
+                       &graphics::Text::new(graphics::TextFragment {
+                           text: journal_text,
+                           color: Some(graphics::Color::from_rgb(90, 90, 90)),
+                           font: Some(font),
+                           scale: Some(graphics::Scale { x: 18.0, y: 18.0 }),
+                       }),
+                       (journal_pos,))?;
+
+        let mut budget_text = format!("Biomass: {}\nBones: {}", resources.biomass, resources.bones);
+        if let Some(cost) = cost {
+            budget_text = format!("{}\n\nThis part: {}\nSo far: {}", budget_text, cost, self.get_spent());
+        }
+        budget_text = format!("{}\n\nSpace when done building", budget_text);
+        let budget_pos = mint::Point2 { x: SCREEN_SIZE.0-230.0, y: 10.0 };
+        graphics::draw(ctx,
+                       &graphics::Text::new(graphics::TextFragment {
+                           text: budget_text,
+                           color: Some(graphics::BLACK),
+                           font: Some(font),
+                           scale: Some(graphics::Scale { x: 18.0, y: 18.0 }),
+                       }),
+                       (budget_pos,))?;
+
         let text_pos = mint::Point2 { x: 5.0, y: SCREEN_SIZE.1-96.0 };
         graphics::draw(ctx,
                        &graphics::Text::new(graphics::TextFragment {
@@ -203,7 +616,7 @@ impl BuilderState {
         };
         if self.curr_choices[0].is_some() {
             let head = &self.possible_heads[self.curr_choices[0].unwrap()];
-            graphics::draw(ctx, head.get_image(), (head_point,))?;
+            graphics::draw(ctx, head.get_image(&self.sprites), (head_point,))?;
         }
         if self.curr_choices[1].is_some() {
             let body_point = mint::Point2 {
@@ -211,7 +624,7 @@ impl BuilderState {
                 y: (head_point.y + 64.0),
             };
             let body = &self.possible_bodies[self.curr_choices[1].unwrap()];
-            graphics::draw(ctx, body.get_image(), (body_point,))?;
+            graphics::draw(ctx, body.get_image(&self.sprites), (body_point,))?;
         }
         if self.curr_choices[2].is_some() {
             let arm_point = mint::Point2 {
@@ -219,7 +632,7 @@ impl BuilderState {
                 y: head_point.y + 64.0,
             };
             let arm = &self.possible_arms[self.curr_choices[2].unwrap()];
-            graphics::draw(ctx, arm.get_image(), (arm_point,))?;
+            graphics::draw(ctx, arm.get_image(&self.sprites), (arm_point,))?;
 
             let other_arm_point = mint::Point2 {
                 x: head_point.x + 128.0,
@@ -227,7 +640,7 @@ impl BuilderState {
             };
             graphics::draw(
                 ctx,
-                arm.get_image(),
+                arm.get_image(&self.sprites),
                 graphics::DrawParam::from((other_arm_point,)).scale([-1.0, 1.0]),
             )?;
         }
@@ -237,23 +650,32 @@ impl BuilderState {
                 y: head_point.y + (64.0 * 2.0),
             };
             let leg = &self.possible_legs[self.curr_choices[3].unwrap()];
-            graphics::draw(ctx, leg.get_image(), (leg_point,))?;
+            graphics::draw(ctx, leg.get_image(&self.sprites), (leg_point,))?;
         }
         Ok(())
     }
 
-    fn draw_options<T: Sprite>(&self, parts: &Vec<T>, ctx: &mut Context) -> GameResult {
+    /// parts still locked are greyed out, and ones that would take the monster over what's
+    /// left in `resources` faded out
+    fn draw_options<T: Part>(&self, parts: &[T], ctx: &mut Context, journal: &Journal, resources: Resources) -> GameResult {
         for (i, part) in parts.iter().enumerate() {
-            let img = part.get_image();
+            let img = part.get_image(&self.sprites);
             let new_point = mint::Point2 {
                 x: i as f32 * 64.0,
                 y: SCREEN_SIZE.1 - 64.0,
             };
-            graphics::draw(ctx, img, (new_point,))?;
+            let color = if !self.tech_tree.is_unlocked(part.get_id(), journal.get_progress()) {
+                graphics::Color::new(0.2, 0.2, 0.2, 0.6)
+            } else if resources.can_afford(self.get_spent() + part.get_cost()) {
+                graphics::WHITE
+            } else {
+                graphics::Color::new(1.0, 1.0, 1.0, 0.3)
+            };
+            graphics::draw(ctx, img, graphics::DrawParam::from((new_point,)).color(color))?;
         }
 
         let outline_rect = graphics::Rect::new(
-            self.curr_hover as f32 * 64.0,
+            self.get_hover() as f32 * 64.0,
             SCREEN_SIZE.1 - 64.0,
             64.0,
             64.0,
@@ -269,63 +691,253 @@ impl BuilderState {
         Ok(())
     }
 
+    /// behaviours have no sprite, so they're listed by name
+    fn draw_behaviour_options(&self, ctx: &mut Context, font: graphics::Font) -> GameResult {
+        for (i, behaviour) in BEHAVIOURS.iter().enumerate() {
+            let marker = if i == self.get_hover() { ">" } else { " " };
+            let option_text = graphics::Text::new(graphics::TextFragment {
+                text: format!("{} {}", marker, behaviour.get_name()),
+                color: Some(graphics::BLACK),
+                font: Some(font),
+                scale: Some(graphics::Scale { x: 20.0, y: 20.0 }),
+            });
+            let option_point = mint::Point2 {
+                x: i as f32 * 150.0,
+                y: SCREEN_SIZE.1 - 40.0,
+            };
+            graphics::draw(ctx, &option_text, (option_point,))?;
+        }
+        Ok(())
+    }
+
     pub fn move_option(&mut self, next: bool) {
+        let hover = self.get_hover();
+        self.curr_hover = if next {
+            (hover + 1).min(self.get_option_count() - 1)
+        } else {
+            hover.saturating_sub(1)
+        };
+    }
+
+    /// how many options there are to choose from at the current stage
+    fn get_option_count(&self) -> usize {
         if self.curr_choices[0].is_none() {
-            if next && self.curr_hover < self.possible_heads.len() - 1 {
-                self.curr_hover += 1
-            } else if !next && self.curr_hover > 0 {
-                self.curr_hover -= 1
-            }
+            self.possible_heads.len()
         } else if self.curr_choices[1].is_none() {
-            if next && self.curr_hover < self.possible_bodies.len() - 1 {
-                self.curr_hover += 1
-            } else if !next && self.curr_hover > 0 {
-                self.curr_hover -= 1
-            }
+            self.possible_bodies.len()
         } else if self.curr_choices[2].is_none() {
-            if next && self.curr_hover < self.possible_arms.len() - 1 {
-                self.curr_hover += 1
-            } else if !next && self.curr_hover > 0 {
-                self.curr_hover -= 1
-            }
+            self.possible_arms.len()
+        } else if self.curr_choices[3].is_none() {
+            self.possible_legs.len()
         } else {
-            if next && self.curr_hover < self.possible_legs.len() - 1 {
-                self.curr_hover += 1
-            } else if !next && self.curr_hover > 0 {
-                self.curr_hover -= 1
-            }
+            BEHAVIOURS.len()
+        }
+    }
+
+    /// the highlighted option, kept within the current stage's options, as the stages
+    /// share the one hover and don't all have as many
+    fn get_hover(&self) -> usize {
+        self.curr_hover.min(self.get_option_count() - 1)
+    }
+
+    /// whether any whole monster of unlocked parts can be grown from `resources`
+    pub fn can_afford_a_monster(&self, resources: Resources, journal: &Journal) -> bool {
+        let progress = journal.get_progress();
+        let costs = |parts: Vec<&dyn Part>| -> Vec<Resources> {
+            parts
+                .into_iter()
+                .filter(|part| self.tech_tree.is_unlocked(part.get_id(), progress))
+                .map(|part| part.get_cost())
+                .collect()
+        };
+        let heads = costs(self.possible_heads.iter().map(|part| part as &dyn Part).collect());
+        let bodies = costs(self.possible_bodies.iter().map(|part| part as &dyn Part).collect());
+        let arms = costs(self.possible_arms.iter().map(|part| part as &dyn Part).collect());
+        let legs = costs(self.possible_legs.iter().map(|part| part as &dyn Part).collect());
+        // biomass and bones can't both be kept lowest at once, so every combination is tried
+        heads.iter().any(|&head| {
+            bodies.iter().any(|&body| {
+                arms.iter()
+                    .any(|&arm| legs.iter().any(|&leg| resources.can_afford(head + body + arm + leg)))
+            })
+        })
+    }
+
+    /// what the parts chosen so far cost between them
+    pub fn get_spent(&self) -> Resources {
+        let mut spent = Resources::default();
+        if let Some(head) = self.curr_choices[0] {
+            spent += self.possible_heads[head].get_cost();
+        }
+        if let Some(body) = self.curr_choices[1] {
+            spent += self.possible_bodies[body].get_cost();
+        }
+        if let Some(arms) = self.curr_choices[2] {
+            spent += self.possible_arms[arms].get_cost();
+        }
+        if let Some(legs) = self.curr_choices[3] {
+            spent += self.possible_legs[legs].get_cost();
+        }
+        spent
+    }
+
+    /// the hovered option, none if it's a behaviour
+    fn get_hovered_part(&self) -> Option<&dyn Part> {
+        if self.curr_choices[0].is_none() {
+            Some(&self.possible_heads[self.get_hover()])
+        } else if self.curr_choices[1].is_none() {
+            Some(&self.possible_bodies[self.get_hover()])
+        } else if self.curr_choices[2].is_none() {
+            Some(&self.possible_arms[self.get_hover()])
+        } else if self.curr_choices[3].is_none() {
+            Some(&self.possible_legs[self.get_hover()])
+        } else {
+            None
         }
     }
 
-    /// choose currently hovered body part
-    pub fn choose_current_and_reset(&mut self) {
+    /// the name of any part by id, for the player to read in unlock conditions
+    fn get_part_name(&self, id: &str) -> String {
+        let heads = self.possible_heads.iter().map(|part| part as &dyn Part);
+        let bodies = self.possible_bodies.iter().map(|part| part as &dyn Part);
+        let arms = self.possible_arms.iter().map(|part| part as &dyn Part);
+        let legs = self.possible_legs.iter().map(|part| part as &dyn Part);
+        heads
+            .chain(bodies)
+            .chain(arms)
+            .chain(legs)
+            .find(|part| part.get_id() == id)
+            .map_or_else(|| id.to_string(), |part| part.get_name().to_string())
+    }
+
+    /// choose currently hovered body part, unless it's still locked in `journal`'s progress
+    /// or the monster would then cost more than `resources`
+    pub fn choose_current_and_reset(&mut self, resources: Resources, journal: &Journal) {
+        if let Some(part) = self.get_hovered_part() {
+            if !self.tech_tree.is_unlocked(part.get_id(), journal.get_progress())
+                || !resources.can_afford(self.get_spent() + part.get_cost())
+            {
+                return;
+            }
+        }
         if self.curr_choices[0].is_none() {
-            self.curr_choices[0] = Some(self.curr_hover);
+            self.curr_choices[0] = Some(self.get_hover());
         } else if self.curr_choices[1].is_none() {
-            self.curr_choices[1] = Some(self.curr_hover);
+            self.curr_choices[1] = Some(self.get_hover());
         } else if self.curr_choices[2].is_none() {
-            self.curr_choices[2] = Some(self.curr_hover);
+            self.curr_choices[2] = Some(self.get_hover());
+        } else if self.curr_choices[3].is_none() {
+            self.curr_choices[3] = Some(self.get_hover());
         } else {
-            self.curr_choices[3] = Some(self.curr_hover);
+            self.curr_choices[4] = Some(self.get_hover());
         }
         self.curr_hover = 0;
     }
 
     pub fn is_fully_selected(&self) -> bool {
-        return self.curr_choices[3].is_some();
+        self.curr_choices[4].is_some()
     }
 
     /// only should be called once you know it's built!
-    pub fn get_built_monster(&self) -> (Head, Body, Arms, Legs) {
+    pub fn get_built_monster(&self) -> (Head, Body, Arms, Legs, &'static dyn Behaviour) {
         (
             self.possible_heads[self.curr_choices[0].unwrap()].clone(),
             self.possible_bodies[self.curr_choices[1].unwrap()].clone(),
             self.possible_arms[self.curr_choices[2].unwrap()].clone(),
             self.possible_legs[self.curr_choices[3].unwrap()].clone(),
+            BEHAVIOURS[self.curr_choices[4].unwrap()],
         )
     }
 
+    /// drops whatever has been chosen, back to the first head
     pub fn empty_choices(&mut self) {
-        self.curr_choices = [None, None, None, None];
+        self.curr_choices = [None, None, None, None, None];
+        self.curr_hover = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::DamageType;
+
+    fn ids(prefix: &str, count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{}-{}", prefix, i)).collect()
+    }
+
+    /// fewer heads than behaviours, and more arms than heads, as a catalogue is free to have
+    fn builder() -> BuilderState {
+        let attack = Attack {
+            damage: 10.0,
+            damage_type: DamageType::Blunt,
+            crit_chance: 0.0,
+        };
+        BuilderState::new(
+            ids("head", 2).into_iter().map(|id| Head::new(id.clone(), id, 0, 100.0, 1.0)).collect(),
+            ids("body", 2)
+                .into_iter()
+                .map(|id| Body::new(id.clone(), id, 0, 100.0, 0.0, Resistances::default()))
+                .collect(),
+            ids("arms", 3).into_iter().map(|id| Arms::new(id.clone(), id, 0, attack, 40.0, None, None)).collect(),
+            ids("legs", 2).into_iter().map(|id| Legs::new(id.clone(), id, 0, 100.0, 0.0)).collect(),
+            PartSprites {
+                heads: Vec::new(),
+                bodies: Vec::new(),
+                arms: Vec::new(),
+                legs: Vec::new(),
+            },
+            TechTree::default(),
+        )
+    }
+
+    fn get_hovered_id(builder: &BuilderState) -> Option<&str> {
+        builder.get_hovered_part().map(|part| part.get_id())
+    }
+
+    #[test]
+    fn the_hover_stays_within_the_stage_it_is_on() {
+        let mut builder = builder();
+        for _ in 0..5 {
+            builder.move_option(true);
+        }
+        assert_eq!(get_hovered_id(&builder), Some("head-1"));
+
+        // on to the legs with the hover left on the last of the three arms
+        builder.curr_choices = [Some(0), Some(0), Some(2), None, None];
+        builder.curr_hover = 2;
+        assert_eq!(get_hovered_id(&builder), Some("legs-1"));
+        builder.move_option(false);
+        assert_eq!(get_hovered_id(&builder), Some("legs-0"));
+    }
+
+    #[test]
+    fn dropping_a_half_built_monster_starts_again_from_the_first_head() {
+        let mut builder = builder();
+        builder.curr_choices = [Some(0), Some(0), Some(0), Some(0), None];
+        for _ in 0..BEHAVIOURS.len() {
+            builder.move_option(true);
+        }
+        assert_eq!(builder.get_hover(), BEHAVIOURS.len() - 1);
+
+        // as ending the day with Space does
+        builder.empty_choices();
+        assert_eq!(builder.curr_hover, 0);
+        assert_eq!(get_hovered_id(&builder), Some("head-0"));
+    }
+
+    #[test]
+    fn resting_mid_build_leaves_the_next_day_on_the_first_head() {
+        let mut builder = builder();
+        builder.curr_choices = [Some(1), Some(1), None, None, None];
+        builder.move_option(true);
+        builder.move_option(true);
+        // the last of the arms, past where the heads stop
+        assert_eq!(get_hovered_id(&builder), Some("arms-2"));
+
+        // resting, like starting or continuing a run, drops the monster being built
+        builder.empty_choices();
+        assert_eq!(get_hovered_id(&builder), Some("head-0"));
+        builder.move_option(true);
+        assert_eq!(get_hovered_id(&builder), Some("head-1"));
     }
 }
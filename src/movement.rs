@@ -0,0 +1,297 @@
+//! Steering helpers shared by humans and monsters.
+//!
+//! Headings are in radians using screen coordinates: 0 points right (+x) and
+//! PI/2 points down (+y), always kept within (-PI, PI].
+
+use cgmath::{InnerSpace, Vector2};
+use ggez::graphics::Rect;
+use std::f32::consts::PI;
+
+pub fn to_vector(point: mint::Point2<f32>) -> Vector2<f32> {
+    Vector2::new(point.x, point.y)
+}
+
+pub fn to_point(vector: Vector2<f32>) -> mint::Point2<f32> {
+    mint::Point2 { x: vector.x, y: vector.y }
+}
+
+pub fn distance(from: mint::Point2<f32>, to: mint::Point2<f32>) -> f32 {
+    (to_vector(to) - to_vector(from)).magnitude()
+}
+
+/// brings any angle back into (-PI, PI]
+pub fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped <= -PI {
+        wrapped + 2.0 * PI
+    } else {
+        wrapped
+    }
+}
+
+/// the heading that points from `from` straight at `to`
+pub fn heading_towards(from: mint::Point2<f32>, to: mint::Point2<f32>) -> f32 {
+    let direction = to_vector(to) - to_vector(from);
+    direction.y.atan2(direction.x)
+}
+
+/// how far `from` has to turn to face `to`, negative meaning anticlockwise on screen
+pub fn angle_difference(from: f32, to: f32) -> f32 {
+    wrap_angle(to - from)
+}
+
+/// turns from `current` towards `target` the short way round, by at most `max_turn`
+pub fn turn_towards(current: f32, target: f32, max_turn: f32) -> f32 {
+    let difference = angle_difference(current, target);
+    if difference.abs() <= max_turn {
+        wrap_angle(target)
+    } else {
+        wrap_angle(current + max_turn.copysign(difference))
+    }
+}
+
+/// moves `distance` along `heading`
+pub fn step_along(pos: mint::Point2<f32>, heading: f32, distance: f32) -> mint::Point2<f32> {
+    to_point(to_vector(pos) + Vector2::new(heading.cos(), heading.sin()) * distance)
+}
+
+/// moves up to `distance` straight at `target`, stopping on it rather than overshooting
+pub fn step_towards(pos: mint::Point2<f32>, target: mint::Point2<f32>, distance: f32) -> mint::Point2<f32> {
+    let offset = to_vector(target) - to_vector(pos);
+    let remaining = offset.magnitude();
+    if remaining <= distance || remaining == 0.0 {
+        target
+    } else {
+        to_point(to_vector(pos) + offset.normalize() * distance)
+    }
+}
+
+/// moves from `from` to `to`, sliding along any obstacle in the way rather than stopping
+/// dead, or stepping sideways around it when walking straight into it. `hitbox_at` gives
+/// the unit's hitbox at a position. Obstacles the unit already overlaps don't block it,
+/// so nothing can get stuck inside one.
+pub fn slide(
+    from: mint::Point2<f32>,
+    to: mint::Point2<f32>,
+    hitbox_at: impl Fn(mint::Point2<f32>) -> Rect,
+    obstacles: &[Rect],
+) -> mint::Point2<f32> {
+    let start = hitbox_at(from);
+    let is_blocked = |pos: mint::Point2<f32>| {
+        let hitbox = hitbox_at(pos);
+        obstacles.iter().any(|obstacle| obstacle.overlaps(&hitbox) && !obstacle.overlaps(&start))
+    };
+    if !is_blocked(to) {
+        return to;
+    }
+
+    // go round the obstacle by the nearer side, stepping across the way the unit was going.
+    // Which side that is only depends on where the obstacle is, so it doesn't dither
+    let step = to_vector(to) - to_vector(from);
+    let mut sideways = Vector2::new(step.y, -step.x);
+    let blocker = obstacles
+        .iter()
+        .find(|obstacle| obstacle.overlaps(&hitbox_at(to)) && !obstacle.overlaps(&start));
+    if let Some(blocker) = blocker {
+        let to_blocker = to_vector(center_of(blocker)) - to_vector(center_of(&start));
+        if sideways.dot(to_blocker) > 0.0 {
+            sideways = -sideways;
+        }
+    }
+    for &side in &[sideways, -sideways] {
+        let around = to_point(to_vector(from) + side);
+        if !is_blocked(around) {
+            return around;
+        }
+    }
+
+    // hemmed in on both sides, so keep whatever part of the move is free
+    let along_x = mint::Point2 { x: to.x, y: from.y };
+    let along_y = mint::Point2 { x: from.x, y: to.y };
+    [along_x, along_y]
+        .iter()
+        .copied()
+        .filter(|&pos| !is_blocked(pos))
+        .max_by(|a, b| distance(from, *a).partial_cmp(&distance(from, *b)).unwrap())
+        .unwrap_or(from)
+}
+
+fn center_of(rect: &Rect) -> mint::Point2<f32> {
+    mint::Point2 {
+        x: rect.x + rect.w / 2.0,
+        y: rect.y + rect.h / 2.0,
+    }
+}
+
+/// how far each of `positions` should move so that none are closer than `min_distance`,
+/// each pair splitting the overlap between them
+pub fn separation(positions: &[mint::Point2<f32>], min_distance: f32) -> Vec<Vector2<f32>> {
+    let mut pushes = vec![Vector2::new(0.0, 0.0); positions.len()];
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            let offset = to_vector(positions[j]) - to_vector(positions[i]);
+            let distance = offset.magnitude();
+            if distance >= min_distance {
+                continue;
+            }
+            // units right on top of each other are split sideways, the first to the left
+            let direction = if distance > 0.0 { offset / distance } else { Vector2::unit_x() };
+            let push = direction * (min_distance - distance) / 2.0;
+            pushes[i] -= push;
+            pushes[j] += push;
+        }
+    }
+    pushes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn point(x: f32, y: f32) -> mint::Point2<f32> {
+        mint::Point2 { x, y }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < EPSILON, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn heading_towards_each_axis() {
+        let origin = point(10.0, 10.0);
+        assert_close(heading_towards(origin, point(20.0, 10.0)), 0.0);
+        assert_close(heading_towards(origin, point(10.0, 20.0)), PI / 2.0);
+        assert_close(heading_towards(origin, point(0.0, 10.0)), PI);
+        assert_close(heading_towards(origin, point(10.0, 0.0)), -PI / 2.0);
+    }
+
+    #[test]
+    fn heading_towards_each_quadrant() {
+        let origin = point(0.0, 0.0);
+        assert_close(heading_towards(origin, point(5.0, 5.0)), PI / 4.0);
+        assert_close(heading_towards(origin, point(-5.0, 5.0)), 3.0 * PI / 4.0);
+        assert_close(heading_towards(origin, point(-5.0, -5.0)), -3.0 * PI / 4.0);
+        assert_close(heading_towards(origin, point(5.0, -5.0)), -PI / 4.0);
+    }
+
+    #[test]
+    fn wrap_angle_stays_in_range() {
+        assert_close(wrap_angle(3.0 * PI), PI);
+        assert_close(wrap_angle(-PI), PI);
+        assert_close(wrap_angle(5.0 * PI / 2.0), PI / 2.0);
+        assert_close(wrap_angle(-5.0 * PI / 2.0), -PI / 2.0);
+        assert_close(wrap_angle(0.5), 0.5);
+    }
+
+    #[test]
+    fn turn_towards_takes_the_short_way_across_pi() {
+        let current = 170.0f32.to_radians();
+        let target = -170.0f32.to_radians();
+        // 20 degrees apart across PI, not 340 degrees the long way
+        assert_close(angle_difference(current, target), 20.0f32.to_radians());
+        let turned = turn_towards(current, target, 10.0f32.to_radians());
+        assert_close(turned, PI);
+        let turned = turn_towards(turned, target, 10.0f32.to_radians());
+        assert_close(turned, target);
+    }
+
+    #[test]
+    fn turn_towards_limits_each_turn() {
+        for &target in &[PI / 4.0, 3.0 * PI / 4.0, -3.0 * PI / 4.0, -PI / 4.0] {
+            let turned = turn_towards(0.0, target, 0.1);
+            assert_close(turned, 0.1f32.copysign(target));
+        }
+    }
+
+    #[test]
+    fn turn_towards_snaps_when_close_enough() {
+        assert_close(turn_towards(1.0, 1.05, 0.1), 1.05);
+        assert_close(turn_towards(-3.1, 3.1, 0.1), 3.1);
+    }
+
+    #[test]
+    fn step_towards_each_quadrant() {
+        let origin = point(100.0, 100.0);
+        let step = 2.0f32.sqrt();
+        for &(dx, dy) in &[(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)] {
+            let target = point(100.0 + dx * 50.0, 100.0 + dy * 50.0);
+            let moved = step_towards(origin, target, step);
+            assert_close(moved.x, 100.0 + dx);
+            assert_close(moved.y, 100.0 + dy);
+        }
+    }
+
+    #[test]
+    fn step_towards_does_not_overshoot() {
+        let moved = step_towards(point(0.0, 0.0), point(3.0, 4.0), 10.0);
+        assert_close(moved.x, 3.0);
+        assert_close(moved.y, 4.0);
+
+        let still = step_towards(point(1.0, 1.0), point(1.0, 1.0), 5.0);
+        assert_close(still.x, 1.0);
+        assert_close(still.y, 1.0);
+    }
+
+    fn square_at(pos: mint::Point2<f32>) -> Rect {
+        Rect::new(pos.x, pos.y, 10.0, 10.0)
+    }
+
+    #[test]
+    fn slide_moves_freely_without_obstacles() {
+        let moved = slide(point(0.0, 0.0), point(5.0, 5.0), square_at, &[]);
+        assert_close(moved.x, 5.0);
+        assert_close(moved.y, 5.0);
+    }
+
+    #[test]
+    fn slide_goes_round_the_near_side_of_an_obstacle() {
+        // walking right into a tree that mostly hangs below, so the way round is up
+        let low_tree = [Rect::new(12.0, -2.0, 10.0, 30.0)];
+        let moved = slide(point(0.0, 0.0), point(5.0, 0.0), square_at, &low_tree);
+        assert_close(moved.x, 0.0);
+        assert_close(moved.y, -5.0);
+
+        let high_tree = [Rect::new(12.0, -18.0, 10.0, 30.0)];
+        let moved = slide(point(0.0, 0.0), point(5.0, 0.0), square_at, &high_tree);
+        assert_close(moved.x, 0.0);
+        assert_close(moved.y, 5.0);
+    }
+
+    #[test]
+    fn slide_keeps_the_free_part_of_a_move_when_hemmed_in() {
+        let corridor = [Rect::new(-12.0, -100.0, 10.0, 200.0), Rect::new(12.0, -100.0, 10.0, 200.0)];
+        let moved = slide(point(0.0, 0.0), point(3.0, 5.0), square_at, &corridor);
+        assert_close(moved.x, 0.0);
+        assert_close(moved.y, 5.0);
+    }
+
+    #[test]
+    fn slide_lets_units_walk_out_of_an_obstacle() {
+        let obstacle = [Rect::new(0.0, 0.0, 32.0, 32.0)];
+        let moved = slide(point(5.0, 5.0), point(-5.0, -5.0), square_at, &obstacle);
+        assert_close(moved.x, -5.0);
+        assert_close(moved.y, -5.0);
+    }
+
+    #[test]
+    fn separation_splits_stacked_units() {
+        let pushes = separation(&[point(0.0, 0.0), point(0.0, 0.0), point(100.0, 0.0)], 10.0);
+        assert_close(pushes[0].x, -5.0);
+        assert_close(pushes[1].x, 5.0);
+        assert_close(pushes[2].magnitude(), 0.0);
+
+        let pushes = separation(&[point(0.0, 0.0), point(0.0, 6.0)], 10.0);
+        assert_close(pushes[0].y, -2.0);
+        assert_close(pushes[1].y, 2.0);
+    }
+
+    #[test]
+    fn step_along_matches_heading() {
+        let moved = step_along(point(0.0, 0.0), heading_towards(point(0.0, 0.0), point(-3.0, 4.0)), 5.0);
+        assert_close(moved.x, -3.0);
+        assert_close(moved.y, 4.0);
+        assert_close(distance(point(0.0, 0.0), moved), 5.0);
+    }
+}
@@ -0,0 +1,272 @@
+//! Finding a way across the night map around the trees, with A* over a grid of tiles.
+
+use crate::movement;
+use crate::SCREEN_SIZE;
+use ggez::graphics::Rect;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub const TILE_SIZE: f32 = 16.0;
+
+// A* costs, a diagonal step being about 1.4 of a straight one
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Which tiles of the night map a unit of one size can stand on. Each kind of unit
+/// gets its own grid, since a tall monster can't fit everywhere a human can.
+pub struct NavGrid {
+    columns: usize,
+    rows: usize,
+    blocked: Vec<bool>, // row by row
+}
+
+/// A path being followed, kept until whatever it leads to moves to another tile.
+#[derive(Debug, Clone, Default)]
+pub struct Route {
+    waypoints: Vec<mint::Point2<f32>>, // the next one to head for is last
+    goal_tile: Option<(usize, usize)>,
+}
+
+impl Route {
+    /// the points still to be walked through, in order, ending at the goal
+    pub fn get_waypoints(&self) -> impl Iterator<Item = &mint::Point2<f32>> {
+        self.waypoints.iter().rev()
+    }
+
+    pub fn clear(&mut self) {
+        self.waypoints.clear();
+        self.goal_tile = None;
+    }
+}
+
+impl NavGrid {
+    /// `hitbox_at` gives the unit's hitbox at a position, a tile being blocked
+    /// if the unit can't stand in the middle of it without touching an obstacle
+    pub fn new(obstacles: &[Rect], hitbox_at: impl Fn(mint::Point2<f32>) -> Rect) -> Self {
+        let columns = (SCREEN_SIZE.0 / TILE_SIZE).ceil() as usize;
+        let rows = (SCREEN_SIZE.1 / TILE_SIZE).ceil() as usize;
+        let mut blocked = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let hitbox = hitbox_at(tile_center((column, row)));
+                blocked.push(obstacles.iter().any(|obstacle| obstacle.overlaps(&hitbox)));
+            }
+        }
+        Self { columns, rows, blocked }
+    }
+
+    /// the tile `pos` is in, clamped to the map
+    pub fn tile_of(&self, pos: mint::Point2<f32>) -> (usize, usize) {
+        let column = (pos.x / TILE_SIZE).max(0.0) as usize;
+        let row = (pos.y / TILE_SIZE).max(0.0) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    pub fn is_blocked(&self, (column, row): (usize, usize)) -> bool {
+        self.blocked[row * self.columns + column]
+    }
+
+    /// the blocked tiles, for drawing
+    pub fn get_blocked_tiles(&self) -> Vec<Rect> {
+        (0..self.blocked.len())
+            .filter(|&i| self.blocked[i])
+            .map(|i| {
+                let (column, row) = (i % self.columns, i / self.columns);
+                Rect::new(column as f32 * TILE_SIZE, row as f32 * TILE_SIZE, TILE_SIZE, TILE_SIZE)
+            })
+            .collect()
+    }
+
+    /// the point to head for next on the way to `goal`, working the route out again
+    /// if the goal has moved to another tile. Waypoints within `reach` count as reached.
+    pub fn steer(&self, route: &mut Route, pos: mint::Point2<f32>, goal: mint::Point2<f32>, reach: f32) -> mint::Point2<f32> {
+        let goal_tile = self.tile_of(goal);
+        if route.goal_tile != Some(goal_tile) || route.waypoints.is_empty() {
+            // with no way through, walking straight at it and sliding is the best there is
+            let mut waypoints = self.find_path(pos, goal).unwrap_or_else(|| vec![goal]);
+            waypoints.reverse();
+            route.waypoints = waypoints;
+            route.goal_tile = Some(goal_tile);
+        }
+        // the goal can wander about inside its tile without a new route
+        route.waypoints[0] = goal;
+        while route.waypoints.len() > 1 && movement::distance(pos, route.waypoints[route.waypoints.len() - 1]) <= reach {
+            route.waypoints.pop();
+        }
+        route.waypoints[route.waypoints.len() - 1]
+    }
+
+    /// the corners to walk through to get from `from` to `to`, ending with `to` itself.
+    /// The start and goal tiles are always allowed, so a unit brushing a tree isn't stranded.
+    pub fn find_path(&self, from: mint::Point2<f32>, to: mint::Point2<f32>) -> Option<Vec<mint::Point2<f32>>> {
+        let start = self.index_of(self.tile_of(from));
+        let goal = self.index_of(self.tile_of(to));
+        let passable = |index: usize| index == start || index == goal || !self.blocked[index];
+
+        let mut costs = vec![u32::MAX; self.blocked.len()];
+        let mut came_from = vec![usize::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0;
+        open.push(Reverse((self.estimate(start, goal), start)));
+
+        while let Some(Reverse((_, current))) = open.pop() {
+            if current == goal {
+                break;
+            }
+            let (column, row) = (current % self.columns, current / self.columns);
+            for (d_column, d_row) in &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let (next_column, next_row) = (column as i32 + d_column, row as i32 + d_row);
+                if next_column < 0 || next_row < 0 || next_column >= self.columns as i32 || next_row >= self.rows as i32 {
+                    continue;
+                }
+                let next = next_row as usize * self.columns + next_column as usize;
+                if !passable(next) {
+                    continue;
+                }
+                let diagonal = *d_column != 0 && *d_row != 0;
+                if diagonal {
+                    // no cutting corners past a blocked tile
+                    let beside = row * self.columns + next_column as usize;
+                    let above_or_below = next_row as usize * self.columns + column;
+                    if !passable(beside) || !passable(above_or_below) {
+                        continue;
+                    }
+                }
+                let cost = costs[current] + if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+                if cost < costs[next] {
+                    costs[next] = cost;
+                    came_from[next] = current;
+                    open.push(Reverse((cost + self.estimate(next, goal), next)));
+                }
+            }
+        }
+        if costs[goal] == u32::MAX {
+            return None;
+        }
+
+        let mut tiles = Vec::new();
+        let mut current = came_from[goal];
+        while current != start && current != usize::MAX {
+            tiles.push(tile_center((current % self.columns, current / self.columns)));
+            current = came_from[current];
+        }
+        tiles.reverse();
+        tiles.push(to);
+        Some(self.smooth(from, tiles))
+    }
+
+    /// skips every waypoint that can be walked past in a straight line
+    fn smooth(&self, from: mint::Point2<f32>, points: Vec<mint::Point2<f32>>) -> Vec<mint::Point2<f32>> {
+        let mut smoothed = Vec::new();
+        let mut anchor = from;
+        let mut i = 0;
+        while i < points.len() {
+            let mut furthest = points.len() - 1;
+            while furthest > i && !self.has_clear_line(anchor, points[furthest]) {
+                furthest -= 1;
+            }
+            smoothed.push(points[furthest]);
+            anchor = points[furthest];
+            i = furthest + 1;
+        }
+        smoothed
+    }
+
+    fn has_clear_line(&self, from: mint::Point2<f32>, to: mint::Point2<f32>) -> bool {
+        let samples = (movement::distance(from, to) / (TILE_SIZE / 4.0)).ceil() as usize;
+        (0..=samples).all(|sample| {
+            let t = if samples == 0 { 0.0 } else { sample as f32 / samples as f32 };
+            let pos = mint::Point2 {
+                x: from.x + (to.x - from.x) * t,
+                y: from.y + (to.y - from.y) * t,
+            };
+            !self.is_blocked(self.tile_of(pos))
+        })
+    }
+
+    fn index_of(&self, (column, row): (usize, usize)) -> usize {
+        row * self.columns + column
+    }
+
+    /// octile distance, which never overestimates on an 8-way grid
+    fn estimate(&self, from: usize, to: usize) -> u32 {
+        let d_column = (from % self.columns).abs_diff(to % self.columns) as u32;
+        let d_row = (from / self.columns).abs_diff(to / self.columns) as u32;
+        STRAIGHT_COST * d_column.max(d_row) + (DIAGONAL_COST - STRAIGHT_COST) * d_column.min(d_row)
+    }
+}
+
+fn tile_center((column, row): (usize, usize)) -> mint::Point2<f32> {
+    mint::Point2 {
+        x: (column as f32 + 0.5) * TILE_SIZE,
+        y: (row as f32 + 0.5) * TILE_SIZE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32, y: f32) -> mint::Point2<f32> {
+        mint::Point2 { x, y }
+    }
+
+    fn dot_at(pos: mint::Point2<f32>) -> Rect {
+        Rect::new(pos.x - 1.0, pos.y - 1.0, 2.0, 2.0)
+    }
+
+    #[test]
+    fn open_ground_is_a_straight_line() {
+        let grid = NavGrid::new(&[], dot_at);
+        let path = grid.find_path(point(40.0, 40.0), point(400.0, 300.0)).unwrap();
+        assert_eq!(path, vec![point(400.0, 300.0)]);
+    }
+
+    #[test]
+    fn paths_go_round_a_wall() {
+        let wall = [Rect::new(200.0, 0.0, 32.0, 500.0)];
+        let grid = NavGrid::new(&wall, dot_at);
+        let path = grid.find_path(point(100.0, 100.0), point(300.0, 100.0)).unwrap();
+        assert_eq!(*path.last().unwrap(), point(300.0, 100.0));
+        // has to dip below the wall to get past it
+        assert!(path.iter().any(|waypoint| waypoint.y > 500.0));
+        let mut from = point(100.0, 100.0);
+        for &waypoint in &path {
+            assert!(grid.has_clear_line(from, waypoint));
+            from = waypoint;
+        }
+    }
+
+    #[test]
+    fn no_path_through_a_solid_wall() {
+        let wall = [Rect::new(200.0, -10.0, 32.0, SCREEN_SIZE.1 + 20.0)];
+        let grid = NavGrid::new(&wall, dot_at);
+        assert!(grid.find_path(point(100.0, 100.0), point(300.0, 100.0)).is_none());
+    }
+
+    #[test]
+    fn units_inside_an_obstacle_can_still_leave() {
+        let tree = [Rect::new(90.0, 90.0, 32.0, 32.0)];
+        let grid = NavGrid::new(&tree, dot_at);
+        assert!(grid.is_blocked(grid.tile_of(point(100.0, 100.0))));
+        assert!(grid.find_path(point(100.0, 100.0), point(300.0, 300.0)).is_some());
+    }
+
+    #[test]
+    fn routes_are_only_worked_out_again_when_the_goal_changes_tile() {
+        let wall = [Rect::new(200.0, 0.0, 32.0, 500.0)];
+        let grid = NavGrid::new(&wall, dot_at);
+        let mut route = Route::default();
+        let first = grid.steer(&mut route, point(100.0, 100.0), point(300.0, 100.0), 2.0);
+        assert!(first.y > 100.0);
+
+        // moving the goal within its tile just moves the end of the route
+        grid.steer(&mut route, point(100.0, 100.0), point(302.0, 102.0), 2.0);
+        assert_eq!(route.get_waypoints().last(), Some(&point(302.0, 102.0)));
+        assert_eq!(route.goal_tile, Some(grid.tile_of(point(300.0, 100.0))));
+
+        // with nothing in the way now, the new route is straight there
+        let next = grid.steer(&mut route, point(100.0, 100.0), point(100.0, 400.0), 2.0);
+        assert_eq!(next, point(100.0, 400.0));
+    }
+}
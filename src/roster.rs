@@ -0,0 +1,232 @@
+use crate::monster::Monster;
+use crate::simulation::Simulation;
+use crate::veterancy;
+use crate::SCREEN_SIZE;
+use ggez::event::KeyCode;
+use ggez::{graphics, Context, GameResult};
+
+const ROW_HEIGHT: f32 = 50.0;
+const VISIBLE_ROWS: usize = 8;
+const MAX_NAME_LENGTH: usize = 16;
+const HEALS_PER_DAY: u32 = 1;
+const SUMMARY_ROWS: usize = 8; // monsters listed with their health while building
+const SUMMARY_ROW_HEIGHT: f32 = 22.0;
+
+/// The screen between building a monster and the night, for looking after the ones you have.
+pub struct RosterState {
+    selected: usize,
+    renaming: Option<String>, // the name typed so far
+    confirming_retire: bool,
+    heals_left: u32,
+    message: String,
+}
+
+impl RosterState {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            renaming: None,
+            confirming_retire: false,
+            heals_left: HEALS_PER_DAY,
+            message: String::new(),
+        }
+    }
+
+    /// called each time the roster opens, once a day
+    pub fn open(&mut self) {
+        *self = Self::new();
+    }
+
+    /// true while typing a name or confirming a retirement, when escape should cancel instead of quit
+    pub fn is_busy(&self) -> bool {
+        self.renaming.is_some() || self.confirming_retire
+    }
+
+    /// returns true once the player wants the night to begin
+    pub fn key_down(&mut self, keycode: KeyCode, simulation: &mut Simulation) -> bool {
+        let count = simulation.get_monsters().len();
+        if let Some(name) = &mut self.renaming {
+            match keycode {
+                KeyCode::Back => {
+                    name.pop();
+                }
+                KeyCode::Return => {
+                    let name = name.trim().to_string();
+                    if !name.is_empty() {
+                        simulation.rename_monster(self.selected, name);
+                    }
+                    self.renaming = None;
+                }
+                KeyCode::Escape => self.renaming = None,
+                _ => (),
+            }
+            return false;
+        }
+        if self.confirming_retire {
+            if keycode == KeyCode::Y {
+                let name = simulation.get_monsters()[self.selected].get_name().to_string();
+                simulation.retire_monster(self.selected);
+                self.selected = self.selected.min(count.saturating_sub(2));
+                self.message = format!("{} has retired.", name);
+            }
+            self.confirming_retire = false;
+            return false;
+        }
+
+        match keycode {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::H if count > 0 => {
+                let monster = &simulation.get_monsters()[self.selected];
+                if self.heals_left == 0 {
+                    self.message = "No heals left today.".to_string();
+                } else if monster.get_hp() >= monster.get_total_hp() {
+                    self.message = format!("{} is already at full health.", monster.get_name());
+                } else {
+                    self.message = format!("{} is healed.", monster.get_name());
+                    simulation.heal_monster(self.selected);
+                    self.heals_left -= 1;
+                }
+            }
+            KeyCode::R if count > 0 => self.confirming_retire = true,
+            KeyCode::F2 if count > 0 => {
+                self.renaming = Some(simulation.get_monsters()[self.selected].get_name().to_string());
+            }
+            KeyCode::Return => {
+                if count > 0 {
+                    return true;
+                }
+                self.message = "You need at least one monster to face the night.".to_string();
+            }
+            _ => (),
+        }
+        false
+    }
+
+    pub fn text_input(&mut self, ch: char) {
+        if let Some(name) = &mut self.renaming {
+            if !ch.is_control() && name.chars().count() < MAX_NAME_LENGTH {
+                name.push(ch);
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, font: graphics::Font, monsters: &[Monster]) -> GameResult {
+        let title_point = mint::Point2 { x: 10.0, y: 10.0 };
+        graphics::draw(ctx, &roster_text("Your monsters", font, 40.0, graphics::BLACK), (title_point,))?;
+
+        // scroll so the selected monster is always on screen
+        let first_row = (self.selected + 1).saturating_sub(VISIBLE_ROWS);
+        for (i, monster) in monsters.iter().enumerate().skip(first_row).take(VISIBLE_ROWS) {
+            let row_y = 70.0 + (i - first_row) as f32 * ROW_HEIGHT;
+            if i == self.selected {
+                let outline_rect = graphics::Rect::new(5.0, row_y - 4.0, SCREEN_SIZE.0 - 10.0, ROW_HEIGHT - 4.0);
+                let mesh = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.0),
+                    outline_rect,
+                    graphics::BLACK,
+                )?;
+                graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+            }
+
+            let name = match &self.renaming {
+                Some(new_name) if i == self.selected => format!("{}_", new_name),
+                _ => monster.get_name().to_string(),
+            };
+            let details = format!(
+                "{} ({})\n{}, {}, {}, {}",
+                name,
+                monster.get_behaviour().get_name(),
+                monster.get_head().get_name(),
+                monster.get_body().get_name(),
+                monster.get_arms().get_name(),
+                monster.get_legs().get_name(),
+            );
+            graphics::draw(
+                ctx,
+                &roster_text(&details, font, 18.0, graphics::BLACK),
+                (mint::Point2 { x: 15.0, y: row_y },),
+            )?;
+
+            let stats = format!(
+                "{:.0}/{:.0} hp\n{} kills, {}",
+                monster.get_hp(),
+                monster.get_total_hp(),
+                monster.get_kills(),
+                veterancy::get_rank(monster.get_level()),
+            );
+            graphics::draw(
+                ctx,
+                &roster_text(&stats, font, 18.0, graphics::BLACK),
+                (mint::Point2 { x: SCREEN_SIZE.0 - 250.0, y: row_y },),
+            )?;
+
+            let pos = mint::Point2 { x: SCREEN_SIZE.0 - 120.0, y: row_y + 5.0 };
+            draw_hp_bar(ctx, monster, pos, 100.0)?;
+        }
+
+        let help = if self.renaming.is_some() {
+            "Type a name, enter to keep it, escape to cancel".to_string()
+        } else if self.confirming_retire {
+            format!("Retire {}? They won't come back. (Y/N)", monsters[self.selected].get_name())
+        } else {
+            format!(
+                "Up/down: choose   H: heal ({} left today)   R: retire   F2: rename   Enter: start the night",
+                self.heals_left
+            )
+        };
+        let help_text = format!("{}\n{}", self.message, help);
+        graphics::draw(
+            ctx,
+            &roster_text(&help_text, font, 18.0, graphics::BLACK),
+            (mint::Point2 { x: 10.0, y: SCREEN_SIZE.1 - 60.0 },),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl Default for RosterState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// the monsters there already and how healthy they are, for the building screen
+pub fn draw_health_summary(ctx: &mut Context, font: graphics::Font, monsters: &[Monster], pos: mint::Point2<f32>) -> GameResult {
+    if monsters.is_empty() {
+        return Ok(());
+    }
+    graphics::draw(ctx, &roster_text("Your monsters:", font, 16.0, graphics::BLACK), (pos,))?;
+    for (i, monster) in monsters.iter().take(SUMMARY_ROWS).enumerate() {
+        let row_y = pos.y + (i + 1) as f32 * SUMMARY_ROW_HEIGHT;
+        let text = format!("{} {:.0}/{:.0}", monster.get_name(), monster.get_hp(), monster.get_total_hp());
+        graphics::draw(ctx, &roster_text(&text, font, 16.0, graphics::BLACK), (mint::Point2 { x: pos.x + 64.0, y: row_y },))?;
+        draw_hp_bar(ctx, monster, mint::Point2 { x: pos.x, y: row_y + 4.0 }, 56.0)?;
+    }
+    if monsters.len() > SUMMARY_ROWS {
+        let more = format!("and {} more", monsters.len() - SUMMARY_ROWS);
+        let row_y = pos.y + (SUMMARY_ROWS + 1) as f32 * SUMMARY_ROW_HEIGHT;
+        graphics::draw(ctx, &roster_text(&more, font, 16.0, graphics::BLACK), (mint::Point2 { x: pos.x, y: row_y },))?;
+    }
+    Ok(())
+}
+
+/// `length` is how long it is at full health
+fn draw_hp_bar(ctx: &mut Context, monster: &Monster, pos: mint::Point2<f32>, length: f32) -> GameResult {
+    let health_height = 10;
+    let health_rect = graphics::Image::solid(ctx, health_height, graphics::Color::from_rgb(0, 200, 0))?;
+    let actual_length = (length*(monster.get_hp()/monster.get_total_hp())) as u16;
+    let x_scaling = actual_length as f32/health_height as f32; // health_height is also width
+    graphics::draw(ctx, &health_rect, graphics::DrawParam::from((pos,)).scale([x_scaling, 1.0]))
+}
+
+fn roster_text(text: &str, font: graphics::Font, size: f32, color: graphics::Color) -> graphics::Text {
+    graphics::Text::new(graphics::TextFragment {
+        text: text.to_string(),
+        color: Some(color),
+        font: Some(font),
+        scale: Some(graphics::Scale { x: size, y: size }),
+    })
+}
@@ -0,0 +1,279 @@
+use crate::behaviour::{self, Behaviour};
+use crate::economy::{Resources, STARTING_RESOURCES};
+use crate::monster::{self, Monster};
+use crate::simulation::Simulation;
+use crate::sprite_loader::PartCatalogue;
+use ggez::{filesystem, Context, GameError, GameResult};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// lives in the user data directory, not the resources
+pub const SAVE_PATH: &str = "/save.ron";
+
+/// A campaign in progress, as it stands at the start of a day.
+/// Monster positions aren't kept, they're rerolled at dawn anyway.
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub day: u16,
+    pub seed: u64,
+    pub rng: Pcg32,
+    pub monsters: Vec<SavedMonster>,
+    /// saves from before there was anything to gather start off with a new run's worth
+    #[serde(default = "starting_resources")]
+    pub resources: Resources,
+}
+
+fn starting_resources() -> Resources {
+    STARTING_RESOURCES
+}
+
+/// Parts are stored by catalogue id so reordering the catalogue doesn't break saves.
+#[derive(Serialize, Deserialize)]
+pub struct SavedMonster {
+    #[serde(default)]
+    pub name: String, // given the next free number if empty, as in saves from before there were names
+    pub head: String,
+    pub body: String,
+    pub arms: String,
+    pub legs: String,
+    #[serde(default)]
+    pub behaviour: String, // the default behaviour if empty, as in saves from before there were others
+    pub hp: f32,
+    #[serde(default)]
+    pub kills: u32,
+    #[serde(default)]
+    pub xp: f32, // its level comes from this
+}
+
+impl SaveGame {
+    pub fn capture(day: u16, seed: u64, resources: Resources, simulation: &Simulation) -> Self {
+        Self {
+            day,
+            seed,
+            rng: simulation.get_rng().clone(),
+            monsters: simulation.get_monsters().iter().map(SavedMonster::from_monster).collect(),
+            resources,
+        }
+    }
+
+    /// rebuilds the saved roster, failing if a part no longer exists in the catalogue
+    pub fn get_monsters(&self, catalogue: &PartCatalogue) -> GameResult<Vec<Monster>> {
+        let mut monsters = self
+            .monsters
+            .iter()
+            .map(|monster| monster.to_monster(catalogue))
+            .collect::<GameResult<Vec<_>>>()?;
+        // saves from before monsters had names leave them blank, so they're numbered like new ones
+        for i in 0..monsters.len() {
+            if monsters[i].name.is_empty() {
+                monsters[i].name = monster::get_unused_name(&monsters);
+            }
+        }
+        Ok(monsters)
+    }
+}
+
+impl SavedMonster {
+    pub fn from_monster(monster: &Monster) -> Self {
+        Self {
+            name: monster.name.clone(),
+            head: monster.head.get_id().to_string(),
+            body: monster.body.get_id().to_string(),
+            arms: monster.arms.get_id().to_string(),
+            legs: monster.legs.get_id().to_string(),
+            behaviour: monster.behaviour.get_id().to_string(),
+            hp: monster.hp,
+            kills: monster.kills,
+            xp: monster.xp,
+        }
+    }
+
+    pub fn to_monster(&self, catalogue: &PartCatalogue) -> GameResult<Monster> {
+        let head = catalogue.find_head(&self.head).ok_or_else(|| missing_part("head", &self.head))?;
+        let body = catalogue.find_body(&self.body).ok_or_else(|| missing_part("body", &self.body))?;
+        let arms = catalogue.find_arms(&self.arms).ok_or_else(|| missing_part("arms", &self.arms))?;
+        let legs = catalogue.find_legs(&self.legs).ok_or_else(|| missing_part("legs", &self.legs))?;
+        let behaviour: &'static dyn Behaviour = if self.behaviour.is_empty() {
+            behaviour::default_behaviour()
+        } else {
+            behaviour::find_behaviour(&self.behaviour).ok_or_else(|| missing_part("behaviour", &self.behaviour))?
+        };
+
+        let mut monster = Monster::new(
+            self.name.clone(),
+            head.clone(),
+            body.clone(),
+            arms.clone(),
+            legs.clone(),
+            behaviour,
+            mint::Point2 { x: 0.0, y: 0.0 },
+        );
+        monster.kills = self.kills;
+        monster.xp = self.xp;
+        monster.hp = self.hp.min(monster.get_total_hp());
+        Ok(monster)
+    }
+}
+
+fn missing_part(kind: &str, id: &str) -> GameError {
+    GameError::ResourceLoadError(format!("{}: unknown {} \"{}\"", SAVE_PATH, kind, id))
+}
+
+pub fn has_save(ctx: &Context) -> bool {
+    filesystem::is_file(ctx, SAVE_PATH)
+}
+
+pub fn save_game(ctx: &mut Context, save: &SaveGame) -> GameResult {
+    let text = ron::ser::to_string_pretty(save, ron::ser::PrettyConfig::default())
+        .map_err(|error| GameError::ResourceLoadError(format!("{}: {}", SAVE_PATH, error)))?;
+    let mut file = filesystem::create(ctx, SAVE_PATH)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+pub fn load_game(ctx: &mut Context) -> GameResult<SaveGame> {
+    let file = filesystem::open(ctx, SAVE_PATH)?;
+    ron::de::from_reader(file)
+        .map_err(|error| GameError::ResourceLoadError(format!("{}: {}", SAVE_PATH, error)))
+}
+
+pub fn delete_save(ctx: &mut Context) -> GameResult {
+    if has_save(ctx) {
+        filesystem::delete(ctx, SAVE_PATH)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Attack, DamageType, Resistances};
+    use crate::monster_build::{Arms, Body, Head, Legs, PartSprites};
+    use crate::waves::WaveScript;
+    use rand::Rng;
+
+    fn catalogue() -> PartCatalogue {
+        let attack = Attack {
+            damage: 10.0,
+            damage_type: DamageType::Blunt,
+            crit_chance: 0.0,
+        };
+        let head = |id: &str| Head::new(id.to_string(), id.to_string(), 0, 100.0, 1.0);
+        let body = |id: &str| Body::new(id.to_string(), id.to_string(), 0, 100.0, 0.0, Resistances::default());
+        let arms = |id: &str| Arms::new(id.to_string(), id.to_string(), 0, attack, 40.0, None, None);
+        let legs = |id: &str| Legs::new(id.to_string(), id.to_string(), 0, 100.0, 0.0);
+        PartCatalogue {
+            heads: vec![head("head-a"), head("head-b")],
+            bodies: vec![body("body-a"), body("body-b")],
+            arms: vec![arms("arms-a"), arms("arms-b")],
+            legs: vec![legs("legs-a"), legs("legs-b")],
+            sprites: PartSprites {
+                heads: Vec::new(),
+                bodies: Vec::new(),
+                arms: Vec::new(),
+                legs: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn saves_bring_back_the_roster_and_random_state() {
+        let catalogue = catalogue();
+        let mut simulation = Simulation::new(Vec::new(), WaveScript { nights: Vec::new() }, 5);
+        let mut scarred = Monster::new(
+            "Scarred".to_string(),
+            catalogue.heads[1].clone(),
+            catalogue.bodies[0].clone(),
+            catalogue.arms[1].clone(),
+            catalogue.legs[0].clone(),
+            behaviour::default_behaviour(),
+            mint::Point2 { x: 0.0, y: 0.0 },
+        );
+        scarred.hp = 42.0;
+        let mut killer = Monster::new(
+            "Killer".to_string(),
+            catalogue.heads[0].clone(),
+            catalogue.bodies[1].clone(),
+            catalogue.arms[0].clone(),
+            catalogue.legs[1].clone(),
+            behaviour::default_behaviour(),
+            mint::Point2 { x: 0.0, y: 0.0 },
+        );
+        killer.kills = 3;
+        simulation.add_monster_at(scarred);
+        simulation.add_monster_at(killer);
+
+        let save = SaveGame::capture(4, 5, Resources::default(), &simulation);
+        let text = ron::ser::to_string(&save).unwrap();
+        let loaded: SaveGame = ron::de::from_str(&text).unwrap();
+        assert_eq!(loaded.day, 4);
+
+        let check = |monsters: Vec<Monster>| {
+            let ids: Vec<_> = monsters
+                .iter()
+                .map(|monster| {
+                    (
+                        monster.head.get_id().to_string(),
+                        monster.body.get_id().to_string(),
+                        monster.arms.get_id().to_string(),
+                        monster.legs.get_id().to_string(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                ids,
+                vec![
+                    ("head-b".to_string(), "body-a".to_string(), "arms-b".to_string(), "legs-a".to_string()),
+                    ("head-a".to_string(), "body-b".to_string(), "arms-a".to_string(), "legs-b".to_string()),
+                ]
+            );
+            assert_eq!(monsters[0].hp, 42.0);
+            assert_eq!(monsters[1].hp, 100.0);
+            assert_eq!(monsters[1].kills, 3);
+        };
+        check(loaded.get_monsters(&catalogue).unwrap());
+
+        // parts are found by id, so shuffling the catalogue changes nothing
+        let mut reordered = catalogue.clone();
+        reordered.heads.reverse();
+        reordered.bodies.reverse();
+        reordered.arms.reverse();
+        reordered.legs.reverse();
+        check(loaded.get_monsters(&reordered).unwrap());
+
+        // and the run carries on drawing the same numbers
+        let mut original = simulation.get_rng().clone();
+        let mut restored = loaded.rng.clone();
+        assert_eq!(original.gen::<u64>(), restored.gen::<u64>());
+
+        reordered.heads.pop();
+        assert!(loaded.get_monsters(&reordered).is_err());
+    }
+
+    #[test]
+    fn saves_from_before_names_number_their_monsters() {
+        let catalogue = catalogue();
+        let mut simulation = Simulation::new(Vec::new(), WaveScript { nights: Vec::new() }, 5);
+        for name in &["", "Monster 1", ""] {
+            simulation.add_monster_at(Monster::new(
+                name.to_string(),
+                catalogue.heads[0].clone(),
+                catalogue.bodies[0].clone(),
+                catalogue.arms[0].clone(),
+                catalogue.legs[0].clone(),
+                behaviour::default_behaviour(),
+                mint::Point2 { x: 0.0, y: 0.0 },
+            ));
+        }
+        let text = ron::ser::to_string(&SaveGame::capture(2, 5, Resources::default(), &simulation)).unwrap();
+        // as it was written before the name was saved at all
+        let old_text = text.replace("name:\"\",", "");
+        assert_ne!(text, old_text);
+
+        let loaded: SaveGame = ron::de::from_str(&old_text).unwrap();
+        let monsters = loaded.get_monsters(&catalogue).unwrap();
+        let names: Vec<_> = monsters.iter().map(Monster::get_name).collect();
+        assert_eq!(names, vec!["Monster 2", "Monster 1", "Monster 3"]);
+    }
+}
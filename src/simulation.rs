@@ -0,0 +1,1672 @@
+use crate::ability::{ArmsAbility, BodyAbility, HeadAbility, LegsAbility};
+use crate::behaviour::{Behaviour, Intent, Sighting, Situation};
+use crate::combat::{self, Defence, HitResult};
+use crate::economy::Resources;
+use crate::human_ai::{self, MonsterInfo, Target};
+use crate::journal::Journal;
+use crate::monster::{
+    get_unused_name, human_hitbox_at, monster_hitbox_at, Archetype, Human, Monster, Nest, Order, Projectile, Shooter, HUMAN_SIZE,
+    MONSTER_HEIGHT, MONSTER_WIDTH, NEST_SIZE,
+};
+use crate::monster_build::{Arms, Body, Head, Legs};
+use crate::movement;
+use crate::navigation::NavGrid;
+use crate::status::StatusEffect;
+use crate::veterancy;
+use crate::vision;
+use crate::waves::{Edge, Spawn, SpawnPoint, SpawnUnits, WaveScript, WinCondition};
+use crate::SCREEN_SIZE;
+use ggez::graphics::Rect;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use std::f32::consts::PI;
+
+/// Everything the player can influence during a single tick of the night.
+#[derive(Debug, Clone, Default)]
+pub struct SimInput {
+    /// orders the player has given since the last tick, by monster index
+    pub orders: Vec<(usize, Option<Order>)>,
+}
+
+/// Something that happened during a tick, for the renderer/audio (or a test) to react to.
+/// Indices refer to the unit's position at the moment the event happened.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimEvent {
+    HumanFired { human: usize },
+    MonsterFired { monster: usize },
+    /// a bullet reached a monster, which was at `at`
+    MonsterShot { monster: usize, hit: HitResult, at: mint::Point2<f32> },
+    /// a monster struck at a human up close
+    MonsterHit { monster: usize, human: usize, hit: HitResult, at: mint::Point2<f32> },
+    /// a monster's projectile reached a human
+    HumanHit { human: usize, hit: HitResult, at: mint::Point2<f32> },
+    NestShot { hit: HitResult },
+    MonsterDied { monster: usize },
+    HumanDied { human: usize },
+    /// a monster earned enough experience for its next level, at the monster's center
+    MonsterRankedUp { monster: usize, level: u32, at: mint::Point2<f32> },
+    /// a wounded human ran off the map
+    HumanFled { human: usize },
+    NestDestroyed,
+}
+
+/// how often the night is stepped, whatever the frame rate
+pub const TICKS_PER_SECOND: u32 = 60;
+pub const TICK_SECONDS: f32 = 1.0 / TICKS_PER_SECOND as f32;
+
+const REST_RECOVERY: f32 = 0.5; // share of their hp monsters heal on a day spent resting
+const GUARD_RADIUS: f32 = 90.0; // how far from the nest idle monsters stand
+const ORDER_ARRIVAL_DISTANCE: f32 = 8.0; // how close a monster has to get to where it was sent
+const ORDER_GIVE_UP_TICKS: u32 = TICKS_PER_SECOND / 2; // how long a monster can be stuck before dropping a move order
+const MONSTER_ATTACK_COOLDOWN: f32 = 0.65; // seconds between hits
+const MUZZLE_LENGTH: f32 = 16.0; // how far in front of a human its bullets appear
+const PROJECTILE_RANGE_FACTOR: f32 = 1.5; // projectiles carry a bit past the range they were fired from
+const PROJECTILE_STEP: f32 = 8.0; // longest move between collision checks, so nothing is skipped over
+pub const TREE_SIZE: f32 = 32.0;
+const TREE_CLUMPS: (u32, u32) = (4, 8); // how many clumps of trees grow in the middle of the map
+const CLEARING_LEFT: f32 = 200.0; // no trees this close to the left, so the nest has room to be guarded
+const CLEARING_RIGHT: f32 = 120.0; // nor this close to the right, where most humans come in
+const TREE_GAP: f32 = 40.0; // room left around each tree so units can always squeeze past
+
+/// The night battle without any graphics or audio, advanced one fixed tick of `TICK_SECONDS` at a time.
+pub struct Simulation {
+    monsters: Vec<Monster>,
+    humans: Vec<Human>,
+    projectiles: Vec<Projectile>,
+    nest: Nest,
+    obstacles: Vec<Rect>, // hitboxes of the trees, which units can't walk or shoot through
+    monster_nav: NavGrid,
+    human_nav: NavGrid,
+    archetypes: Vec<Archetype>,
+    waves: WaveScript,
+    rng: Pcg32,
+    day: u16,
+    win_condition: WinCondition,
+    pending_spawns: Vec<Spawn>, // latest first, so the next one due is at the end
+    ticks: u32,                 // since the night started
+    evidence: Journal,          // what the player could have seen the parts do tonight
+    harvest: Resources,         // dropped by the humans killed tonight
+    difficulty: f32,            // how much tougher tonight's humans are, for the roster's levels
+}
+
+impl Simulation {
+    /// the same seed (and the same inputs) will always play out the same nights
+    pub fn new(archetypes: Vec<Archetype>, waves: WaveScript, seed: u64) -> Self {
+        Self {
+            monsters: Vec::new(),
+            humans: Vec::new(),
+            projectiles: Vec::new(),
+            nest: Nest::new(mint::Point2 {
+                x: 20.0,
+                y: (SCREEN_SIZE.1 - NEST_SIZE) / 2.0,
+            }),
+            obstacles: Vec::new(),
+            monster_nav: NavGrid::new(&[], monster_hitbox_at),
+            human_nav: NavGrid::new(&[], human_hitbox_at),
+            archetypes,
+            waves,
+            rng: Pcg32::seed_from_u64(seed),
+            day: 1,
+            win_condition: WinCondition::KillAll,
+            pending_spawns: Vec::new(),
+            ticks: 0,
+            evidence: Journal::new(),
+            harvest: Resources::default(),
+            difficulty: 1.0,
+        }
+    }
+
+    /// throws away the whole run, starting again from the given seed
+    pub fn reset(&mut self, seed: u64) {
+        self.monsters.clear();
+        self.humans.clear();
+        self.projectiles.clear();
+        self.obstacles.clear();
+        self.rebuild_navigation();
+        self.pending_spawns.clear();
+        self.rng = Pcg32::seed_from_u64(seed);
+    }
+
+    pub fn get_monsters(&self) -> &[Monster] {
+        &self.monsters
+    }
+
+    pub fn get_humans(&self) -> &[Human] {
+        &self.humans
+    }
+
+    pub fn get_projectiles(&self) -> &[Projectile] {
+        &self.projectiles
+    }
+
+    pub fn get_nest(&self) -> &Nest {
+        &self.nest
+    }
+
+    pub fn get_obstacles(&self) -> &[Rect] {
+        &self.obstacles
+    }
+
+    /// where humans can walk, for drawing
+    pub fn get_human_nav(&self) -> &NavGrid {
+        &self.human_nav
+    }
+
+    /// seconds since the night started
+    pub fn get_night_time(&self) -> f32 {
+        self.ticks as f32 * TICK_SECONDS
+    }
+
+    /// seconds until the night is survived, if this night has a timer
+    pub fn get_time_left(&self) -> Option<f32> {
+        match self.win_condition {
+            WinCondition::SurviveTimer(duration) | WinCondition::ProtectNest(duration) => {
+                Some((duration - self.get_night_time()).max(0.0))
+            }
+            WinCondition::KillAll => None,
+        }
+    }
+
+    /// hands over everything observed about the parts since the night started
+    pub fn take_evidence(&mut self) -> Journal {
+        std::mem::take(&mut self.evidence)
+    }
+
+    /// hands over everything the humans killed since the night started dropped
+    pub fn take_harvest(&mut self) -> Resources {
+        std::mem::take(&mut self.harvest)
+    }
+
+    /// how many nights the campaign lasts
+    pub fn get_night_count(&self) -> usize {
+        self.waves.nights.len()
+    }
+
+    pub fn get_rng(&self) -> &Pcg32 {
+        &self.rng
+    }
+
+    /// picks a run back up from a save, replacing the roster and random state
+    pub fn restore(&mut self, rng: Pcg32, monsters: Vec<Monster>) {
+        self.monsters = monsters;
+        self.humans.clear();
+        self.projectiles.clear();
+        self.obstacles.clear();
+        self.rebuild_navigation();
+        self.pending_spawns.clear();
+        self.rng = rng;
+    }
+
+    pub fn reset_monster_pos(&mut self) {
+        for monster in &mut self.monsters {
+            let new_point = mint::Point2 { x: 0.0, y: self.rng.gen_range(0.0, SCREEN_SIZE.1-96.0) };
+            monster.pos = new_point;
+        }
+    }
+
+    pub fn add_monster(&mut self, head: Head, body: Body, arms: Arms, legs: Legs, behaviour: &'static dyn Behaviour) {
+        let new_point = mint::Point2 { x: 0.0, y: self.rng.gen_range(0.0, SCREEN_SIZE.1-96.0) };
+        let name = get_unused_name(&self.monsters);
+        self.monsters.push(Monster::new(
+            name,
+            head,
+            body,
+            arms,
+            legs,
+            behaviour,
+            new_point,
+        )); // NOTE: ^monsters are close together^
+    }
+
+    /// patches a monster back up to full health
+    pub fn heal_monster(&mut self, index: usize) {
+        let monster = &mut self.monsters[index];
+        monster.hp = monster.get_total_hp();
+    }
+
+    /// every monster heals as much as its body recovers overnight
+    pub fn recover_at_dawn(&mut self) {
+        for monster in &mut self.monsters {
+            let recovery = monster.body.get_recovery();
+            monster.recover(recovery);
+        }
+    }
+
+    /// a day spent resting rather than building heals every monster a good deal more
+    pub fn rest_monsters(&mut self) {
+        for monster in &mut self.monsters {
+            monster.recover(REST_RECOVERY);
+        }
+    }
+
+    /// sends a monster away for good
+    pub fn retire_monster(&mut self, index: usize) {
+        self.remove_monster(index);
+    }
+
+    /// keeps the projectiles in flight and effects left on humans pointing at the monsters behind them
+    fn remove_monster(&mut self, index: usize) {
+        self.monsters.remove(index);
+        for human in &mut self.humans {
+            human.statuses.remove_source(index);
+            if let Some(Target::Monster(target)) = human.target {
+                human.target = match target {
+                    _ if target == index => None,
+                    _ if target > index => Some(Target::Monster(target - 1)),
+                    _ => human.target,
+                };
+            }
+        }
+        for projectile in &mut self.projectiles {
+            if let Shooter::Monster(Some(shooter)) = projectile.shooter {
+                projectile.shooter = Shooter::Monster(match shooter {
+                    _ if shooter == index => None,
+                    _ if shooter > index => Some(shooter - 1),
+                    _ => Some(shooter),
+                });
+            }
+        }
+    }
+
+    /// keeps attack orders pointing at the same humans
+    fn remove_human(&mut self, index: usize) {
+        self.humans.remove(index);
+        for monster in &mut self.monsters {
+            if let Some(Order::Attack(target)) = monster.order {
+                monster.order = match target {
+                    _ if target == index => None,
+                    _ if target > index => Some(Order::Attack(target - 1)),
+                    _ => monster.order,
+                };
+            }
+        }
+    }
+
+    /// a human has been killed, leaving behind what its kind drops
+    fn kill_human(&mut self, index: usize, events: &mut Vec<SimEvent>) {
+        self.harvest += self.archetypes[self.humans[index].archetype].drops;
+        self.evidence.record_kill();
+        self.remove_human(index);
+        events.push(SimEvent::HumanDied { human: index });
+    }
+
+    /// experience for monster `index` landing a hit, and the kill if it was one
+    fn credit_monster(&mut self, index: usize, damage: f32, killed: bool, events: &mut Vec<SimEvent>) {
+        let monster = &mut self.monsters[index];
+        let mut xp = damage * veterancy::XP_PER_DAMAGE;
+        if killed {
+            monster.kills += 1;
+            xp += veterancy::XP_PER_KILL;
+        }
+        if monster.gain_xp(xp) {
+            events.push(SimEvent::MonsterRankedUp {
+                monster: index,
+                level: monster.get_level(),
+                at: monster.get_center(),
+            });
+        }
+    }
+
+    pub fn rename_monster(&mut self, index: usize, name: String) {
+        self.monsters[index].name = name;
+    }
+
+    /// places a monster exactly, mostly useful for setting up tests
+    pub fn add_monster_at(&mut self, monster: Monster) {
+        self.monsters.push(monster);
+    }
+
+    /// places a human exactly, mostly useful for setting up tests
+    pub fn add_human(&mut self, human: Human) {
+        self.humans.push(human);
+    }
+
+    /// sets up the scripted night for this day, humans then turn up as `step` reaches their spawn times
+    pub fn start_night(&mut self, day: u16) {
+        self.humans.clear();
+        self.projectiles.clear();
+        self.nest.repair();
+        for monster in &mut self.monsters {
+            monster.order = None;
+            monster.reset_statuses();
+        }
+        self.day = day;
+        self.ticks = 0;
+        self.evidence.clear();
+        self.harvest = Resources::default();
+        self.difficulty = veterancy::get_difficulty_factor(self.monsters.iter().map(|monster| monster.get_level()));
+
+        // past the end of the script the last night just repeats
+        let night_index = (usize::from(day) - 1).min(self.waves.nights.len() - 1);
+        let night = &self.waves.nights[night_index];
+        self.win_condition = night.win;
+        self.pending_spawns = night.spawns.clone();
+        // spawn times are checked to be finite when the script loads
+        self.pending_spawns.sort_by(|a, b| b.time.partial_cmp(&a.time).unwrap());
+        self.spawn_due_humans();
+    }
+
+    fn spawn_due_humans(&mut self) {
+        while let Some(spawn) = self.pending_spawns.last() {
+            if spawn.time > self.get_night_time() {
+                break;
+            }
+            let spawn = self.pending_spawns.pop().unwrap();
+            let archetype_indices = match &spawn.units {
+                SpawnUnits::Archetype(id, count) => {
+                    let index = self
+                        .archetypes
+                        .iter()
+                        .position(|archetype| &archetype.id == id)
+                        .expect("wave script archetypes are checked when loading");
+                    vec![index; *count as usize]
+                }
+                SpawnUnits::Budget(budget) => self.compose_wave(*budget),
+            };
+            for archetype_index in archetype_indices {
+                let new_pos = self.get_spawn_pos(spawn.at);
+                let archetype = &self.archetypes[archetype_index];
+                let mut human = Human::new(archetype_index, archetype, new_pos, &mut self.rng);
+                human.toughen(self.difficulty);
+                self.humans.push(human);
+            }
+        }
+    }
+
+    fn get_spawn_pos(&mut self, at: SpawnPoint) -> mint::Point2<f32> {
+        match at {
+            SpawnPoint::Point(x, y) => mint::Point2 { x, y },
+            SpawnPoint::Edge(Edge::Right) => mint::Point2 {
+                x: SCREEN_SIZE.0 - 32.0,
+                y: self.rng.gen_range(0.0, SCREEN_SIZE.1-32.0),
+            },
+            SpawnPoint::Edge(Edge::Left) => mint::Point2 {
+                x: 0.0,
+                y: self.rng.gen_range(0.0, SCREEN_SIZE.1-32.0),
+            },
+            SpawnPoint::Edge(Edge::Top) => mint::Point2 {
+                x: self.rng.gen_range(0.0, SCREEN_SIZE.0-32.0),
+                y: 0.0,
+            },
+            SpawnPoint::Edge(Edge::Bottom) => mint::Point2 {
+                x: self.rng.gen_range(0.0, SCREEN_SIZE.0-32.0),
+                y: SCREEN_SIZE.1 - 32.0,
+            },
+        }
+    }
+
+    /// picks archetypes up to the budget from whichever have turned up by
+    /// the current day, chosen by their weight
+    pub fn compose_wave(&mut self, mut budget: u32) -> Vec<usize> {
+        let mut wave = Vec::new();
+        loop {
+            let day = self.day;
+            let affordable: Vec<usize> = (0..self.archetypes.len())
+                .filter(|&i| self.archetypes[i].first_day <= day && self.archetypes[i].cost <= budget)
+                .collect();
+            let archetypes = &self.archetypes;
+            match affordable.choose_weighted(&mut self.rng, |&i| archetypes[i].weight) {
+                Ok(&choice) => {
+                    budget -= archetypes[choice].cost;
+                    wave.push(choice);
+                }
+                Err(_) => break, // nothing left that fits the budget
+            }
+        }
+        wave
+    }
+
+    /// grows a treeline along the top and bottom edges and clumps of trees across the field
+    pub fn generate_scenery(&mut self) {
+        self.obstacles.clear();
+
+        let y = 0.0;
+        let mut x = 0.0;
+        while x < SCREEN_SIZE.0 {
+            self.obstacles.push(Rect::new(x, y, TREE_SIZE, TREE_SIZE));
+
+            x += self.rng.gen_range(50.0, 100.0);
+        }
+
+        let y = SCREEN_SIZE.1 - TREE_SIZE;
+        let mut x = 0.0;
+        while x < SCREEN_SIZE.0 {
+            self.obstacles.push(Rect::new(x, y, TREE_SIZE, TREE_SIZE));
+
+            x += self.rng.gen_range(50.0, 100.0);
+        }
+
+        let clumps = self.rng.gen_range(TREE_CLUMPS.0, TREE_CLUMPS.1 + 1);
+        for _ in 0..clumps {
+            let center = mint::Point2 {
+                x: self.rng.gen_range(CLEARING_LEFT, SCREEN_SIZE.0 - CLEARING_RIGHT),
+                y: self.rng.gen_range(TREE_SIZE * 3.0, SCREEN_SIZE.1 - TREE_SIZE * 4.0),
+            };
+            let trees = self.rng.gen_range(1, 4);
+            for _ in 0..trees {
+                let tree = Rect::new(
+                    center.x + self.rng.gen_range(-TREE_SIZE * 2.0, TREE_SIZE * 2.0),
+                    center.y + self.rng.gen_range(-TREE_SIZE * 2.0, TREE_SIZE * 2.0),
+                    TREE_SIZE,
+                    TREE_SIZE,
+                );
+                // leave a gap between trees so nothing gets wedged in a corner between them
+                let room = Rect::new(
+                    tree.x - TREE_GAP,
+                    tree.y - TREE_GAP,
+                    TREE_SIZE + TREE_GAP * 2.0,
+                    TREE_SIZE + TREE_GAP * 2.0,
+                );
+                if !self.obstacles.iter().any(|obstacle| obstacle.overlaps(&room)) {
+                    self.obstacles.push(tree);
+                }
+            }
+        }
+
+        self.rebuild_navigation();
+    }
+
+    /// the trees have moved, so every route has to be worked out again
+    fn rebuild_navigation(&mut self) {
+        self.monster_nav = NavGrid::new(&self.obstacles, monster_hitbox_at);
+        self.human_nav = NavGrid::new(&self.obstacles, human_hitbox_at);
+        for monster in &mut self.monsters {
+            monster.route.clear();
+        }
+    }
+
+    /// advances the night by one tick, pushing anything notable onto `events`.
+    /// optionally returns if true if monster won, else false
+    pub fn step(&mut self, input: &SimInput, events: &mut Vec<SimEvent>) -> Option<bool> {
+        self.ticks += 1;
+        self.spawn_due_humans();
+        for &(index, order) in &input.orders {
+            if let Some(monster) = self.monsters.get_mut(index) {
+                monster.order = order;
+                monster.stalled = 0;
+            }
+        }
+
+        self.update_statuses(events);
+        self.update_humans(events);
+        self.update_monsters(events);
+        self.separate_units();
+        self.update_projectiles(events);
+
+        let everyone_killed = self.humans.is_empty() && self.pending_spawns.is_empty();
+        // when protecting the nest, it can hold out on its own after the monsters are gone
+        let monsters_needed = !matches!(self.win_condition, WinCondition::ProtectNest(_));
+        if self.nest.is_destroyed() || (monsters_needed && self.monsters.is_empty()) {
+            Some(false)
+        } else if everyone_killed || self.get_time_left().is_some_and(|time| time <= 0.0) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// wears down everyone's status effects, which can be the end of them
+    fn update_statuses(&mut self, events: &mut Vec<SimEvent>) {
+        let mut i = 0;
+        while i < self.monsters.len() {
+            let monster = &mut self.monsters[i];
+            let resistances = monster.body.get_resistances();
+            let damage = monster.statuses.tick(TICK_SECONDS, &resistances);
+            monster.hp = (monster.hp - damage).min(monster.get_total_hp());
+            if monster.hp <= 0.0 {
+                self.remove_monster(i);
+                events.push(SimEvent::MonsterDied { monster: i });
+            } else {
+                i += 1;
+            }
+        }
+
+        let mut i = 0;
+        while i < self.humans.len() {
+            let human = &mut self.humans[i];
+            // asked before the tick, in case the effect that kills wears off with it
+            let source = human.statuses.get_source();
+            let hp_before = human.hp;
+            let damage = human.statuses.tick(TICK_SECONDS, &human.defence.resistances);
+            human.hp = (human.hp - damage).min(human.total_hp);
+            if human.hp <= 0.0 {
+                self.kill_human(i, events);
+                if let Some(source) = source {
+                    self.credit_monster(source, hp_before, true, events);
+                }
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn update_humans(&mut self, events: &mut Vec<SimEvent>) {
+        let mut i = 0;
+        while i < self.humans.len() {
+            if self.nest.is_destroyed() {
+                break;
+            }
+            if self.humans[i].statuses.is_stunned() {
+                self.humans[i].cooldown = (self.humans[i].cooldown - TICK_SECONDS).max(0.0);
+                i += 1;
+                continue;
+            }
+            let tactics = self.archetypes[self.humans[i].archetype].tactics;
+            if human_ai::should_retreat(&tactics, self.humans[i].hp, self.humans[i].total_hp) {
+                if self.flee(i) {
+                    self.remove_human(i);
+                    events.push(SimEvent::HumanFled { human: i });
+                    continue;
+                }
+                i += 1;
+                continue;
+            }
+
+            let target = self.choose_human_target(i, tactics.targeting);
+            self.humans[i].target = Some(target);
+            let target_pos = match target {
+                Target::Monster(index) => self.monsters[index].get_center(),
+                Target::Nest => self.nest.get_center(),
+            };
+            let distance = movement::distance(self.humans[i].pos, target_pos);
+            let target_heading = movement::heading_towards(self.humans[i].pos, target_pos);
+            let range = self.humans[i].range;
+
+            if distance > range * human_ai::PREFERRED_RANGE {
+                // close in, finding a way round the trees
+                let human = &mut self.humans[i];
+                let pos_before = human.pos;
+                let reach = human.speed * TICK_SECONDS;
+                let goal = human_ai::approach_point(human.pos, target_pos, range, human.flank_angle);
+                let waypoint = self.human_nav.steer(&mut human.route, human.pos, goal, reach);
+                human.look_towards(movement::heading_towards(human.pos, waypoint));
+                human.move_forward();
+                human.pos = movement::slide(pos_before, human.pos, human_hitbox_at, &self.obstacles);
+            } else {
+                if tactics.keep_range && distance < range * human_ai::TOO_CLOSE {
+                    // back off, still facing the target
+                    let human = &mut self.humans[i];
+                    let away = movement::wrap_angle(target_heading + PI);
+                    let to = movement::step_along(human.pos, away, human.speed * human.statuses.get_speed_factor() * human_ai::BACKPEDAL_SPEED * TICK_SECONDS);
+                    let to = mint::Point2 {
+                        x: normalize_x(to.x),
+                        y: normalize_y(to.y),
+                    };
+                    human.pos = movement::slide(human.pos, to, human_hitbox_at, &self.obstacles);
+                    human.route.clear();
+                }
+                self.humans[i].look_towards(target_heading);
+                if distance < range && self.humans[i].is_facing(target_heading) && self.humans[i].cooldown <= 0.0 {
+                    self.human_fire(i, events);
+                }
+            }
+            self.humans[i].cooldown = (self.humans[i].cooldown - TICK_SECONDS).max(0.0);
+            i += 1;
+        }
+    }
+
+    /// what human `index` goes for this tick
+    fn choose_human_target(&self, index: usize, targeting: human_ai::Targeting) -> Target {
+        let human = &self.humans[index];
+        let monsters: Vec<_> = self
+            .monsters
+            .iter()
+            .map(|monster| MonsterInfo {
+                distance: movement::distance(human.pos, monster.get_center()),
+                hp: monster.hp,
+                threat: human_ai::threat_of(monster.get_attack().damage, monster.kills),
+            })
+            .collect();
+        let nest_distance = movement::distance(human.pos, self.nest.get_center());
+        human_ai::choose_target(targeting, &monsters, nest_distance, human.range)
+    }
+
+    /// runs human `index` towards the right edge, where they came from.
+    /// Returns true once they're off the map
+    fn flee(&mut self, index: usize) -> bool {
+        let human = &mut self.humans[index];
+        human.target = None;
+        if human.pos.x >= SCREEN_SIZE.0 - HUMAN_SIZE / 2.0 {
+            return true;
+        }
+        let pos_before = human.pos;
+        let exit = mint::Point2 {
+            x: SCREEN_SIZE.0,
+            y: human.pos.y,
+        };
+        let waypoint = self.human_nav.steer(&mut human.route, human.pos, exit, human.speed * TICK_SECONDS);
+        human.look_towards(movement::heading_towards(human.pos, waypoint));
+        human.move_forward();
+        human.pos = movement::slide(pos_before, human.pos, human_hitbox_at, &self.obstacles);
+        human.cooldown = (human.cooldown - TICK_SECONDS).max(0.0);
+        false
+    }
+
+    fn human_fire(&mut self, index: usize, events: &mut Vec<SimEvent>) {
+        let human = &self.humans[index];
+        let spread = human.spread;
+        let stray = if spread > 0.0 { self.rng.gen_range(-spread, spread) } else { 0.0 };
+        let heading = movement::wrap_angle(human.heading + stray);
+        self.projectiles.push(Projectile::new(
+            Shooter::Human,
+            movement::step_along(human.pos, heading, MUZZLE_LENGTH),
+            heading,
+            human.bullet_speed,
+            human.attack,
+            human.range * PROJECTILE_RANGE_FACTOR,
+            human.on_hit,
+        ));
+        events.push(SimEvent::HumanFired { human: index });
+        self.humans[index].cooldown = self.humans[index].reload;
+    }
+
+    fn update_monsters(&mut self, events: &mut Vec<SimEvent>) {
+        for i in 0..self.monsters.len() {
+            if self.monsters[i].statuses.is_stunned() {
+                self.monsters[i].cooldown = (self.monsters[i].cooldown - TICK_SECONDS).max(0.0);
+                continue;
+            }
+            let pos_before = self.monsters[i].pos;
+            let target = self.get_closest_visible_human(i);
+            if let Some((human, distance)) = target {
+                if !self.monsters[i].has_target {
+                    let distance = self.monsters[i].without_bonus(distance, |bonus| bonus.sight);
+                    self.evidence.record_sighting(self.monsters[i].head.get_id(), distance);
+                }
+                let center = self.monsters[i].get_center();
+                if !vision::is_line_clear(center, self.humans[human].pos, &self.obstacles) {
+                    self.evidence.record_ability(self.monsters[i].head.get_id(), &HeadAbility::XRay);
+                }
+            }
+            self.monsters[i].has_target = target.is_some();
+            let attack_range = self.monsters[i].arms.get_attack_range();
+            let speed = self.monsters[i].get_speed() * self.monsters[i].statuses.get_speed_factor() * TICK_SECONDS;
+
+            match self.monsters[i].order {
+                Some(Order::Move(destination)) => {
+                    let goal = mint::Point2 {
+                        x: destination.x - MONSTER_WIDTH / 2.0,
+                        y: destination.y - MONSTER_HEIGHT / 2.0,
+                    };
+                    self.walk_monster_towards(i, goal, speed);
+                    self.record_movement(i, pos_before);
+                    // done once there, or once it's been stuck a while as close as it can get
+                    let arrived = movement::distance(self.monsters[i].get_center(), destination) <= ORDER_ARRIVAL_DISTANCE;
+                    let monster = &mut self.monsters[i];
+                    monster.stalled = if monster.pos == pos_before { monster.stalled + 1 } else { 0 };
+                    if arrived || monster.stalled >= ORDER_GIVE_UP_TICKS {
+                        monster.order = None;
+                        monster.stalled = 0;
+                    }
+                }
+                Some(Order::Attack(target_index)) if target_index < self.humans.len() => {
+                    // told who to go for, so it doesn't need to be looking their way
+                    let target_pos = self.humans[target_index].pos;
+                    let center = self.monsters[i].get_center();
+                    let distance = movement::distance(center, target_pos);
+                    if distance <= attack_range && vision::is_line_clear(center, target_pos, &self.obstacles) {
+                        self.monster_attack(i, target_index, events);
+                    } else {
+                        self.chase_human(i, target_index, speed);
+                        self.record_movement(i, pos_before);
+                    }
+                }
+                Some(Order::Attack(_)) => self.monsters[i].order = None,
+                Some(Order::Hold) => {
+                    if let Some((target_index, distance)) = target {
+                        if distance <= attack_range {
+                            self.monster_attack(i, target_index, events);
+                        }
+                    }
+                }
+                None => {
+                    let situation = self.get_situation(i, target);
+                    match self.monsters[i].behaviour.decide(&situation) {
+                        Intent::Attack(target_index) => self.monster_attack(i, target_index, events),
+                        Intent::Chase(target_index) => {
+                            self.chase_human(i, target_index, speed);
+                            self.record_movement(i, pos_before);
+                        }
+                        Intent::Retreat(target_index) => self.retreat_from_human(i, target_index, speed),
+                        Intent::Guard => self.guard_nest(i, speed / 2.0),
+                    }
+                }
+            }
+            self.monsters[i].cooldown = (self.monsters[i].cooldown - TICK_SECONDS).max(0.0);
+        }
+    }
+
+    /// turns a monster on a human within its attack range, hitting or spitting if it's ready
+    fn monster_attack(&mut self, index: usize, target_index: usize, events: &mut Vec<SimEvent>) {
+        let center = self.monsters[index].get_center();
+        let target_pos = self.humans[target_index].pos;
+        self.monsters[index].heading = movement::heading_towards(center, target_pos);
+        if self.monsters[index].cooldown > 0.0 {
+            return;
+        }
+
+        match self.monsters[index].arms.get_ranged() {
+            Some(ranged) => {
+                self.projectiles.push(Projectile::new(
+                    Shooter::Monster(Some(index)),
+                    center,
+                    self.monsters[index].heading,
+                    ranged.speed,
+                    self.monsters[index].get_attack(),
+                    ranged.range * PROJECTILE_RANGE_FACTOR,
+                    self.monsters[index].arms.get_on_hit(),
+                ));
+                events.push(SimEvent::MonsterFired { monster: index });
+            }
+            None => {
+                let mut struck = vec![target_index];
+                if let Some(radius) = self.monsters[index].arms.get_cleave() {
+                    let others = (0..self.humans.len()).filter(|&i| {
+                        i != target_index && movement::distance(self.humans[i].pos, target_pos) <= radius
+                    });
+                    struck.extend(others);
+                    if struck.len() > 1 {
+                        self.evidence.record_ability(self.monsters[index].arms.get_id(), &ArmsAbility::Cleave(radius));
+                    }
+                }
+                // from the back, so a death doesn't move the humans still to be hit
+                struck.sort_unstable_by(|a, b| b.cmp(a));
+                for human in struck {
+                    let hit = self.melee_hit(index, human, events);
+                    // only the one it went for counts, so the journal's estimate is of a single blow,
+                    // and of the arms themselves rather than whoever it landed on
+                    if human == target_index && !hit.is_miss() {
+                        let arms = &self.monsters[index].arms;
+                        self.evidence.record_hit(arms.get_id(), arms.get_attack().damage);
+                    }
+                }
+            }
+        }
+        self.monsters[index].cooldown = MONSTER_ATTACK_COOLDOWN;
+    }
+
+    /// one human taking a blow from monster `index`'s arms
+    fn melee_hit(&mut self, index: usize, target_index: usize, events: &mut Vec<SimEvent>) -> HitResult {
+        let target = &mut self.humans[target_index];
+        let hit = combat::resolve_hit(&self.monsters[index].get_attack(), &target.defence, &mut self.rng);
+        events.push(SimEvent::MonsterHit {
+            monster: index,
+            human: target_index,
+            hit,
+            at: target.pos,
+        });
+        if hit.is_miss() {
+            return hit;
+        }
+        // no experience for the overkill
+        let dealt = hit.damage.min(target.hp);
+        target.hp -= hit.damage;
+        let killed = target.hp <= 0.0;
+        if killed {
+            self.kill_human(target_index, events);
+        }
+        self.credit_monster(index, dealt, killed, events);
+        if killed {
+            return hit;
+        }
+        let target = &mut self.humans[target_index];
+        if let Some(effect) = self.monsters[index].arms.get_on_hit() {
+            target.statuses.apply(StatusEffect {
+                source: Some(index),
+                ..effect
+            });
+        }
+        if let Some(distance) = self.monsters[index].arms.get_knockback() {
+            let away = movement::heading_towards(self.monsters[index].get_center(), target.pos);
+            let to = movement::step_along(target.pos, away, distance);
+            let to = mint::Point2 {
+                x: normalize_x(to.x),
+                y: normalize_y(to.y),
+            };
+            let from = target.pos;
+            target.pos = movement::slide(from, to, human_hitbox_at, &self.obstacles);
+            target.route.clear();
+            if target.pos != from {
+                self.evidence.record_ability(self.monsters[index].arms.get_id(), &ArmsAbility::Knockback(distance));
+            }
+        }
+        hit
+    }
+
+    /// what monster `index` can tell about its surroundings, for its behaviour to act on.
+    /// `target` is the closest human it can see and how far away they are
+    fn get_situation(&self, index: usize, target: Option<(usize, f32)>) -> Situation {
+        let monster = &self.monsters[index];
+        let nest_center = self.nest.get_center();
+        let sighting = |(human, distance): (usize, f32)| Sighting {
+            human,
+            distance,
+            nest_distance: movement::distance(nest_center, self.humans[human].pos),
+        };
+        let center = monster.get_center();
+        let closest_human = self
+            .humans
+            .iter()
+            .enumerate()
+            .map(|(i, human)| (i, movement::distance(center, human.pos)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        Situation {
+            hp_fraction: monster.hp / monster.get_total_hp(),
+            target: target.map(sighting),
+            closest_human: closest_human.map(sighting),
+            attack_range: monster.arms.get_attack_range(),
+            ready_to_attack: monster.cooldown <= 0.0,
+        }
+    }
+
+    /// sends a monster back to its own spot around the nest, where it keeps watch
+    fn guard_nest(&mut self, index: usize, speed: f32) {
+        let pos_before = self.monsters[index].pos;
+        let guard_angle = index as f32 * 2.0 * PI / self.monsters.len() as f32;
+        let nest_center = self.nest.get_center();
+        let guard_point = mint::Point2 {
+            x: normalize_x(nest_center.x + GUARD_RADIUS * guard_angle.cos() - MONSTER_WIDTH / 2.0),
+            y: normalize_y(nest_center.y + GUARD_RADIUS * guard_angle.sin() - MONSTER_HEIGHT / 2.0),
+        };
+        self.walk_monster_towards(index, guard_point, speed);
+        if self.monsters[index].pos == pos_before {
+            // once in place, keep watch facing out from the nest
+            let center = self.monsters[index].get_center();
+            self.monsters[index].heading = movement::heading_towards(nest_center, center);
+        }
+    }
+
+    /// backs a monster up to `speed` straight away from a human, keeping an eye on them
+    fn retreat_from_human(&mut self, index: usize, target_index: usize, speed: f32) {
+        let pos_before = self.monsters[index].pos;
+        let center = self.monsters[index].get_center();
+        let target_pos = self.humans[target_index].pos;
+        let away = movement::heading_towards(target_pos, center);
+        let to = movement::step_along(pos_before, away, speed);
+        let to = mint::Point2 {
+            x: normalize_x(to.x),
+            y: normalize_y(to.y),
+        };
+        let monster = &mut self.monsters[index];
+        monster.pos = movement::slide(pos_before, to, monster_hitbox_at, &self.obstacles);
+        monster.heading = movement::heading_towards(monster.get_center(), target_pos);
+        // its route is stale now it's gone off it
+        monster.route.clear();
+        self.record_movement(index, pos_before);
+    }
+
+    /// walks a monster up to `speed` towards a human
+    fn chase_human(&mut self, index: usize, target_index: usize, speed: f32) {
+        // the target is measured from the monster's center, but monsters move by their corner
+        let target_pos = self.humans[target_index].pos;
+        let goal = mint::Point2 {
+            x: target_pos.x - MONSTER_WIDTH / 2.0,
+            y: target_pos.y - MONSTER_HEIGHT / 2.0,
+        };
+        self.walk_monster_towards(index, goal, speed);
+    }
+
+    /// moves a monster up to `speed` along its route to `goal`, staying on the map,
+    /// and turns it to face the way it went
+    fn walk_monster_towards(&mut self, index: usize, goal: mint::Point2<f32>, speed: f32) {
+        let goal = mint::Point2 {
+            x: normalize_x(goal.x),
+            y: normalize_y(goal.y),
+        };
+        let monster = &mut self.monsters[index];
+        let from = monster.pos;
+        if monster.legs.can_jump() {
+            // straight there, over anything in the way
+            monster.pos = movement::step_towards(from, goal, speed);
+            monster.route.clear();
+            let hitbox = monster.get_hitbox();
+            if self.obstacles.iter().any(|obstacle| obstacle.overlaps(&hitbox)) {
+                self.evidence.record_ability(monster.legs.get_id(), &LegsAbility::Jump);
+            }
+        } else {
+            let waypoint = self.monster_nav.steer(&mut monster.route, monster.pos, goal, speed);
+            let to = movement::step_towards(monster.pos, waypoint, speed);
+            monster.pos = movement::slide(from, to, monster_hitbox_at, &self.obstacles);
+        }
+        if monster.pos != from {
+            monster.heading = movement::heading_towards(from, monster.pos);
+        }
+    }
+
+    /// nudges apart units that have bunched up, so they don't end up stacked on one spot
+    fn separate_units(&mut self) {
+        let centers: Vec<_> = self.monsters.iter().map(Monster::get_center).collect();
+        let pushes = movement::separation(&centers, MONSTER_WIDTH);
+        for (monster, push) in self.monsters.iter_mut().zip(pushes) {
+            let pushed = movement::to_point(movement::to_vector(monster.pos) + push);
+            let pushed = mint::Point2 {
+                x: normalize_x(pushed.x),
+                y: normalize_y(pushed.y),
+            };
+            monster.pos = movement::slide(monster.pos, pushed, monster_hitbox_at, &self.obstacles);
+        }
+
+        let positions: Vec<_> = self.humans.iter().map(Human::get_pos).collect();
+        let pushes = movement::separation(&positions, HUMAN_SIZE);
+        for (human, push) in self.humans.iter_mut().zip(pushes) {
+            let pushed = movement::to_point(movement::to_vector(human.pos) + push);
+            human.pos = movement::slide(human.pos, pushed, human_hitbox_at, &self.obstacles);
+        }
+    }
+
+    fn update_projectiles(&mut self, events: &mut Vec<SimEvent>) {
+        let mut i = 0;
+        while i < self.projectiles.len() {
+            if self.move_projectile(i, events) {
+                i += 1;
+            } else {
+                self.projectiles.remove(i);
+            }
+        }
+    }
+
+    /// returns false once the projectile has hit something, been blocked or run out of range
+    fn move_projectile(&mut self, index: usize, events: &mut Vec<SimEvent>) -> bool {
+        let (shooter, heading, attack, effect) = {
+            let projectile = &self.projectiles[index];
+            (projectile.shooter, projectile.heading, projectile.attack, projectile.effect)
+        };
+        let travel = (self.projectiles[index].speed * TICK_SECONDS).min(self.projectiles[index].range_left);
+        let steps = (travel / PROJECTILE_STEP).ceil().max(1.0) as usize;
+        for _ in 0..steps {
+            let pos = movement::step_along(self.projectiles[index].pos, heading, travel / steps as f32);
+            self.projectiles[index].pos = pos;
+
+            let off_map = pos.x < 0.0 || pos.x > SCREEN_SIZE.0 || pos.y < 0.0 || pos.y > SCREEN_SIZE.1;
+            let blocked = self.obstacles.iter().any(|obstacle| obstacle.contains(pos));
+            if off_map || blocked {
+                return false;
+            }
+
+            match shooter {
+                Shooter::Human => {
+                    if let Some(target_index) = self.monsters.iter().position(|monster| monster.get_hitbox().contains(pos)) {
+                        let target = &mut self.monsters[target_index];
+                        let defence = target.get_defence();
+                        let hit = combat::resolve_hit(&attack, &defence, &mut self.rng);
+                        events.push(SimEvent::MonsterShot {
+                            monster: target_index,
+                            hit,
+                            at: target.get_center(),
+                        });
+                        // turn to see where that came from
+                        target.heading = movement::wrap_angle(heading + PI);
+                        if hit.is_miss() {
+                            return false;
+                        }
+                        if defence.armour > 0.0 {
+                            self.evidence.record_ability(target.body.get_id(), &BodyAbility::Armour(defence.armour));
+                        }
+                        target.hp -= hit.damage;
+                        let lost = target.get_total_hp() - target.hp;
+                        let damage_taken = target.without_bonus(lost, |bonus| bonus.health);
+                        self.evidence.record_hit_taken(target.body.get_id(), damage_taken, target.hp > 0.0);
+                        if target.hp <= 0.0 {
+                            self.remove_monster(target_index);
+                            events.push(SimEvent::MonsterDied { monster: target_index });
+                        } else if let Some(effect) = effect {
+                            target.statuses.apply(effect);
+                        }
+                        return false;
+                    }
+                    if !self.nest.is_destroyed() && movement::distance(pos, self.nest.get_center()) <= NEST_SIZE / 2.0 {
+                        // the nest can't dodge, and has nothing to shrug off shots with
+                        let hit = combat::resolve_hit(&attack, &Defence::default(), &mut self.rng);
+                        events.push(SimEvent::NestShot { hit });
+                        self.nest.hp -= hit.damage;
+                        if self.nest.is_destroyed() {
+                            events.push(SimEvent::NestDestroyed);
+                        }
+                        return false;
+                    }
+                }
+                Shooter::Monster(monster_index) => {
+                    if let Some(target_index) = self.humans.iter().position(|human| human.get_hitbox().contains(pos)) {
+                        let target = &mut self.humans[target_index];
+                        let hit = combat::resolve_hit(&attack, &target.defence, &mut self.rng);
+                        events.push(SimEvent::HumanHit {
+                            human: target_index,
+                            hit,
+                            at: target.pos,
+                        });
+                        if hit.is_miss() {
+                            return false;
+                        }
+                        if let Some(monster_index) = monster_index {
+                            let arms = &self.monsters[monster_index].arms;
+                            self.evidence.record_hit(arms.get_id(), arms.get_attack().damage);
+                        }
+                        let target = &mut self.humans[target_index];
+                        let dealt = hit.damage.min(target.hp);
+                        target.hp -= hit.damage;
+                        let killed = target.hp <= 0.0;
+                        if killed {
+                            self.kill_human(target_index, events);
+                        } else if let Some(effect) = effect {
+                            target.statuses.apply(StatusEffect {
+                                source: monster_index,
+                                ..effect
+                            });
+                        }
+                        if let Some(monster_index) = monster_index {
+                            self.credit_monster(monster_index, dealt, killed, events);
+                        }
+                        return false;
+                    }
+                }
+            }
+        }
+
+        self.projectiles[index].range_left -= travel;
+        self.projectiles[index].range_left > 0.0
+    }
+
+    /// only full speed moves count, so the journal's speed estimate isn't dragged down by strolling
+    /// or being slowed
+    fn record_movement(&mut self, monster_index: usize, pos_before: mint::Point2<f32>) {
+        let monster = &self.monsters[monster_index];
+        if monster.statuses.get_speed_factor() < 1.0 {
+            return;
+        }
+        let distance = monster.without_bonus(movement::distance(pos_before, monster.pos), |bonus| bonus.speed);
+        if distance > 0.0 {
+            self.evidence.record_movement(monster.legs.get_id(), distance, TICK_SECONDS);
+        }
+    }
+
+    /// the closest human the monster can see, and how far away they are
+    fn get_closest_visible_human(&self, monster_index: usize) -> Option<(usize, f32)> {
+        let view = self.monsters[monster_index].get_view_cone();
+        // x-ray eyes see straight through trees
+        let obstacles: &[Rect] = if self.monsters[monster_index].head.has_x_ray() { &[] } else { &self.obstacles };
+        self.humans
+            .iter()
+            .enumerate()
+            .filter(|(_, human)| view.can_see(human.pos, obstacles))
+            .map(|(i, human)| (i, movement::distance(view.eye, human.pos)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+    }
+}
+
+fn normalize_x(x: f32) -> f32 {
+    if x < 0.0 {
+        0.0
+    } else if x > SCREEN_SIZE.0 {
+        SCREEN_SIZE.0
+    } else {
+        x
+    }
+}
+
+fn normalize_y(y: f32) -> f32 {
+    if y < 0.0 {
+        0.0
+    } else if y > SCREEN_SIZE.1 {
+        SCREEN_SIZE.1
+    } else {
+        y
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ability::Ability;
+    use crate::combat::Resistances;
+    use crate::human_ai::Tactics;
+    use crate::status::StatusKind;
+    use crate::test_fixtures::{self, attack, point};
+    use crate::waves::Night;
+
+    fn archetype() -> Archetype {
+        test_fixtures::archetype(20.0, 5.0, Tactics::default())
+    }
+
+    fn monster(pos: mint::Point2<f32>) -> Monster {
+        test_fixtures::monster(100.0, 30.0, pos)
+    }
+
+    fn human(pos: mint::Point2<f32>) -> Human {
+        test_fixtures::human(&archetype(), pos)
+    }
+
+    /// an empty field with nothing scripted, so nothing turns up but what the test adds
+    fn simulation() -> Simulation {
+        Simulation::new(vec![archetype()], WaveScript { nights: Vec::new() }, 1)
+    }
+
+    fn step(simulation: &mut Simulation) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        simulation.step(&SimInput::default(), &mut events);
+        events
+    }
+
+    /// a snapshot of everything that moves, to compare runs by
+    fn snapshot(simulation: &Simulation) -> Vec<(f32, f32, f32)> {
+        let monsters = simulation.get_monsters().iter().map(|monster| (monster.pos.x, monster.pos.y, monster.hp));
+        let humans = simulation.get_humans().iter().map(|human| (human.pos.x, human.pos.y, human.hp));
+        monsters.chain(humans).collect()
+    }
+
+    #[test]
+    fn new_monsters_never_share_a_name() {
+        let mut sim = simulation();
+        let add = |sim: &mut Simulation| {
+            let parts = monster(point(0.0, 0.0));
+            sim.add_monster(parts.head, parts.body, parts.arms, parts.legs, parts.behaviour);
+        };
+        for _ in 0..3 {
+            add(&mut sim);
+        }
+        sim.retire_monster(0);
+        add(&mut sim);
+        let names: Vec<_> = sim.get_monsters().iter().map(Monster::get_name).collect();
+        assert_eq!(names, vec!["Monster 2", "Monster 3", "Monster 1"]);
+    }
+
+    #[test]
+    fn the_same_seed_plays_out_the_same_night() {
+        let waves = WaveScript {
+            nights: vec![Night {
+                win: WinCondition::SurviveTimer(30.0),
+                spawns: vec![
+                    Spawn {
+                        time: 0.0,
+                        at: SpawnPoint::Edge(Edge::Right),
+                        units: SpawnUnits::Budget(3),
+                    },
+                    Spawn {
+                        time: 2.0,
+                        at: SpawnPoint::Edge(Edge::Top),
+                        units: SpawnUnits::Archetype("test".to_string(), 2),
+                    },
+                ],
+            }],
+        };
+        let archetype = Archetype {
+            spread: 0.1,
+            crit_chance: 0.2,
+            ..archetype()
+        };
+        let run = |seed: u64| {
+            let mut simulation = Simulation::new(vec![archetype.clone()], waves.clone(), seed);
+            for _ in 0..3 {
+                let template = monster(point(0.0, 0.0));
+                simulation.add_monster(template.head, template.body, template.arms, template.legs, template.behaviour);
+            }
+            simulation.generate_scenery();
+            simulation.start_night(1);
+            let mut history = Vec::new();
+            for _ in 0..600 {
+                let events = step(&mut simulation);
+                history.push((snapshot(&simulation), events));
+            }
+            history
+        };
+        let first = run(7);
+        assert_eq!(first, run(7));
+        assert_ne!(first, run(8));
+    }
+
+    #[test]
+    fn humans_close_in_and_shoot_monsters() {
+        let mut simulation = simulation();
+        let mut target = monster(point(200.0, 250.0));
+        target.order = Some(Order::Hold);
+        simulation.add_monster_at(target);
+        simulation.add_human(human(point(600.0, 300.0)));
+
+        let mut shot = None;
+        for _ in 0..300 {
+            let events = step(&mut simulation);
+            shot = events.into_iter().find(|event| matches!(event, SimEvent::MonsterShot { .. }));
+            if shot.is_some() {
+                break;
+            }
+        }
+        match shot {
+            Some(SimEvent::MonsterShot { monster: 0, hit, .. }) => {
+                assert!((hit.damage - 5.0).abs() < 1e-4);
+                assert!((simulation.get_monsters()[0].get_hp() - 95.0).abs() < 1e-4);
+            }
+            other => panic!("expected the monster to be shot, got {:?}", other),
+        }
+        // it had to walk into range to get the shot off
+        assert!(simulation.get_humans()[0].get_pos().x < 600.0);
+    }
+
+    #[test]
+    fn monsters_kill_humans_in_reach() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(200.0, 250.0)));
+        // just right of the monster's center, well within reach
+        simulation.add_human(human(point(240.0, 300.0)));
+
+        let events = step(&mut simulation);
+        assert!(events.contains(&SimEvent::HumanDied { human: 0 }), "{:?}", events);
+        assert!(simulation.get_humans().is_empty());
+        assert_eq!(simulation.get_monsters()[0].get_kills(), 1);
+    }
+
+    fn bullet(pos: mint::Point2<f32>, range: f32) -> Projectile {
+        Projectile::new(Shooter::Human, pos, 0.0, 600.0, attack(5.0), range, None)
+    }
+
+    #[test]
+    fn bullets_fly_until_they_hit_a_monster() {
+        let mut simulation = simulation();
+        let mut target = monster(point(400.0, 250.0));
+        target.order = Some(Order::Hold);
+        simulation.add_monster_at(target);
+        simulation.projectiles.push(bullet(point(300.0, 300.0), 500.0));
+
+        let events = step(&mut simulation);
+        assert!(simulation.get_projectiles()[0].get_pos().x > 300.0);
+        assert!(events.is_empty(), "{:?}", events);
+
+        let mut hit = false;
+        for _ in 0..30 {
+            if step(&mut simulation).iter().any(|event| matches!(event, SimEvent::MonsterShot { monster: 0, .. })) {
+                hit = true;
+                break;
+            }
+        }
+        assert!(hit);
+        assert!(simulation.get_projectiles().is_empty());
+        assert!((simulation.get_monsters()[0].get_hp() - 95.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn trees_stop_bullets() {
+        let mut simulation = simulation();
+        let mut target = monster(point(400.0, 250.0));
+        target.order = Some(Order::Hold);
+        simulation.add_monster_at(target);
+        simulation.obstacles.push(Rect::new(340.0, 284.0, TREE_SIZE, TREE_SIZE));
+        simulation.rebuild_navigation();
+        simulation.projectiles.push(bullet(point(300.0, 300.0), 500.0));
+
+        for _ in 0..30 {
+            let events = step(&mut simulation);
+            assert!(!events.iter().any(|event| matches!(event, SimEvent::MonsterShot { .. })));
+        }
+        assert!(simulation.get_projectiles().is_empty());
+        assert_eq!(simulation.get_monsters()[0].get_hp(), 100.0);
+    }
+
+    #[test]
+    fn bullets_run_out_of_range() {
+        let mut simulation = simulation();
+        simulation.projectiles.push(bullet(point(100.0, 100.0), 50.0));
+
+        let mut ticks = 0;
+        while !simulation.get_projectiles().is_empty() {
+            let pos = simulation.get_projectiles()[0].get_pos();
+            assert!(pos.x < 150.0 + 1e-3, "{:?}", pos);
+            step(&mut simulation);
+            ticks += 1;
+        }
+        assert_eq!(ticks, (50.0 / (600.0 * TICK_SECONDS)).ceil() as u32);
+    }
+
+    fn order(simulation: &mut Simulation, order: Order) {
+        let input = SimInput {
+            orders: vec![(0, Some(order))],
+        };
+        simulation.step(&input, &mut Vec::new());
+    }
+
+    #[test]
+    fn move_orders_end_on_arrival() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(100.0, 100.0)));
+        let destination = point(400.0, 300.0);
+        order(&mut simulation, Order::Move(destination));
+
+        for _ in 0..300 {
+            if simulation.get_monsters()[0].order.is_none() {
+                break;
+            }
+            step(&mut simulation);
+        }
+        assert_eq!(simulation.get_monsters()[0].order, None);
+        let center = simulation.get_monsters()[0].get_center();
+        assert!(movement::distance(center, destination) <= ORDER_ARRIVAL_DISTANCE, "{:?}", center);
+    }
+
+    #[test]
+    fn move_orders_outlast_a_moment_stuck() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(0.0, 100.0)));
+        // off the map, so it gets as far as the edge and no further
+        order(&mut simulation, Order::Move(point(-200.0, 148.0)));
+        assert_eq!(simulation.get_monsters()[0].get_pos(), point(0.0, 100.0));
+
+        step(&mut simulation);
+        assert!(simulation.get_monsters()[0].order.is_some());
+        for _ in 0..ORDER_GIVE_UP_TICKS {
+            step(&mut simulation);
+        }
+        assert_eq!(simulation.get_monsters()[0].order, None);
+    }
+
+    #[test]
+    fn attack_orders_follow_their_human_when_another_dies() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(100.0, 250.0)));
+        simulation.add_human(human(point(600.0, 100.0)));
+        simulation.add_human(human(point(600.0, 500.0)));
+        order(&mut simulation, Order::Attack(1));
+
+        simulation.kill_human(0, &mut Vec::new());
+        assert_eq!(simulation.get_monsters()[0].order, Some(Order::Attack(0)));
+        let before = movement::distance(simulation.get_monsters()[0].get_center(), simulation.get_humans()[0].pos);
+        for _ in 0..30 {
+            step(&mut simulation);
+        }
+        let after = movement::distance(simulation.get_monsters()[0].get_center(), simulation.get_humans()[0].pos);
+        assert!(after < before);
+
+        // and give up once their human is gone
+        simulation.kill_human(0, &mut Vec::new());
+        assert_eq!(simulation.get_monsters()[0].order, None);
+    }
+
+    #[test]
+    fn holding_monsters_only_fight_what_comes_to_them() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(200.0, 250.0)));
+        simulation.add_human(human(point(600.0, 300.0)));
+        order(&mut simulation, Order::Hold);
+        for _ in 0..120 {
+            step(&mut simulation);
+        }
+        assert_eq!(simulation.get_monsters()[0].get_pos(), point(200.0, 250.0));
+        assert_eq!(simulation.get_monsters()[0].order, Some(Order::Hold));
+        assert_eq!(simulation.get_humans().len(), 1);
+
+        simulation.add_human(human(point(240.0, 300.0)));
+        let events = step(&mut simulation);
+        assert!(events.contains(&SimEvent::HumanDied { human: 1 }), "{:?}", events);
+        assert_eq!(simulation.get_monsters()[0].get_pos(), point(200.0, 250.0));
+    }
+
+    #[test]
+    fn monsters_get_the_kill_when_what_they_left_finishes_a_human() {
+        let mut simulation = simulation();
+        let mut burner = monster(point(200.0, 250.0));
+        let on_hit = StatusEffect {
+            kind: StatusKind::Burning,
+            strength: 100.0,
+            duration: 1.0,
+            source: None,
+        };
+        burner.arms = Arms::new("arms".to_string(), "Arms".to_string(), 0, attack(1.0), 40.0, None, Some(on_hit));
+        simulation.add_monster_at(burner);
+        simulation.add_human(human(point(240.0, 300.0)));
+
+        let mut died = false;
+        for _ in 0..30 {
+            if step(&mut simulation).contains(&SimEvent::HumanDied { human: 0 }) {
+                died = true;
+                break;
+            }
+        }
+        assert!(died);
+        let burner = &simulation.get_monsters()[0];
+        assert_eq!(burner.get_kills(), 1);
+        assert!(burner.get_xp() >= veterancy::XP_PER_KILL);
+    }
+
+    fn arms_with(abilities: Vec<ArmsAbility>) -> Arms {
+        Arms::new("arms".to_string(), "Arms".to_string(), 0, attack(5.0), 40.0, None, None).with_abilities(abilities)
+    }
+
+    #[test]
+    fn cleaving_hits_humans_close_to_the_target() {
+        let mut simulation = simulation();
+        let mut cleaver = monster(point(200.0, 250.0));
+        cleaver.arms = arms_with(vec![ArmsAbility::Cleave(30.0)]);
+        simulation.add_monster_at(cleaver);
+        simulation.add_human(human(point(240.0, 300.0)));
+        simulation.add_human(human(point(240.0, 325.0)));
+        simulation.add_human(human(point(240.0, 380.0)));
+        let cleave = [ArmsAbility::Cleave(30.0)];
+        assert_eq!(simulation.evidence.describe_abilities("arms", &cleave), None);
+
+        step(&mut simulation);
+        let hp: Vec<_> = simulation.get_humans().iter().map(|human| human.hp).collect();
+        assert_eq!(hp, vec![15.0, 15.0, 20.0]);
+        // now it's been seen, the builder can tell the player about it
+        let description = simulation.take_evidence().describe_abilities("arms", &cleave);
+        assert!(description.unwrap().contains(cleave[0].get_name()));
+    }
+
+    #[test]
+    fn knockback_shoves_humans_away() {
+        let mut simulation = simulation();
+        let mut shover = monster(point(200.0, 250.0));
+        shover.arms = arms_with(vec![ArmsAbility::Knockback(40.0)]);
+        simulation.add_monster_at(shover);
+        simulation.add_human(human(point(240.0, 300.0)));
+
+        let events = step(&mut simulation);
+        assert!(events.iter().any(|event| matches!(event, SimEvent::MonsterHit { .. })));
+        assert!(simulation.get_humans()[0].pos.x > 270.0, "{:?}", simulation.get_humans()[0].pos);
+        assert!(simulation.evidence.has_seen_ability("arms", &ArmsAbility::Knockback(40.0)));
+    }
+
+    #[test]
+    fn armour_takes_the_edge_off_shots() {
+        let mut simulation = simulation();
+        let mut target = monster(point(200.0, 250.0));
+        target.body = Body::new("body".to_string(), "Body".to_string(), 0, 100.0, 0.0, Resistances::default())
+            .with_abilities(vec![BodyAbility::Armour(3.0)]);
+        target.order = Some(Order::Hold);
+        simulation.add_monster_at(target);
+        simulation.add_human(human(point(600.0, 300.0)));
+
+        for _ in 0..300 {
+            if step(&mut simulation).iter().any(|event| matches!(event, SimEvent::MonsterShot { .. })) {
+                break;
+            }
+        }
+        assert!((simulation.get_monsters()[0].get_hp() - 98.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn x_ray_eyes_see_through_trees() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(200.0, 250.0)));
+        simulation.add_human(human(point(450.0, 298.0)));
+        simulation.obstacles.push(Rect::new(320.0, 282.0, TREE_SIZE, TREE_SIZE));
+        assert_eq!(simulation.get_closest_visible_human(0), None);
+
+        let head = Head::new("eyes".to_string(), "Eyes".to_string(), 0, 300.0, 2.0 * PI);
+        simulation.monsters[0].head = head.with_abilities(vec![HeadAbility::XRay]);
+        assert_eq!(simulation.get_closest_visible_human(0).map(|(human, _)| human), Some(0));
+    }
+
+    #[test]
+    fn jumping_legs_go_straight_over_trees() {
+        // a wall of trees straight across the way, that a walker has to go round
+        let walk = |legs: Legs| {
+            let mut simulation = simulation();
+            let mut walker = monster(point(200.0, 250.0));
+            walker.legs = legs;
+            simulation.add_monster_at(walker);
+            simulation.obstacles.push(Rect::new(320.0, 200.0, TREE_SIZE, 200.0));
+            simulation.rebuild_navigation();
+            order(&mut simulation, Order::Move(point(500.0, 298.0)));
+            let mut lowest = 250.0_f32;
+            let mut highest = 250.0_f32;
+            for _ in 0..600 {
+                if simulation.get_monsters()[0].order.is_none() {
+                    break;
+                }
+                step(&mut simulation);
+                lowest = lowest.min(simulation.get_monsters()[0].pos.y);
+                highest = highest.max(simulation.get_monsters()[0].pos.y);
+            }
+            let center = simulation.get_monsters()[0].get_center();
+            assert!(movement::distance(center, point(500.0, 298.0)) <= ORDER_ARRIVAL_DISTANCE);
+            (highest - lowest, simulation.take_evidence())
+        };
+
+        let (detour, _) = walk(Legs::new("legs".to_string(), "Legs".to_string(), 0, 120.0, 0.0));
+        assert!(detour > 50.0, "{}", detour);
+
+        let jumper = Legs::new("legs".to_string(), "Legs".to_string(), 0, 120.0, 0.0);
+        let (detour, evidence) = walk(jumper.with_abilities(vec![LegsAbility::Jump]));
+        assert!(detour < 1.0, "{}", detour);
+        assert!(evidence.has_seen_ability("legs", &LegsAbility::Jump));
+    }
+
+    #[test]
+    fn the_journal_learns_what_the_arms_hit_for_not_what_got_through() {
+        let armoured = Archetype {
+            armour: 3.0,
+            ..archetype()
+        };
+        let mut simulation = Simulation::new(vec![armoured.clone()], WaveScript { nights: Vec::new() }, 1);
+        let mut puncher = monster(point(200.0, 250.0));
+        puncher.arms = arms_with(Vec::new());
+        puncher.order = Some(Order::Hold);
+        simulation.add_monster_at(puncher);
+        simulation.add_human(Human::new(0, &armoured, point(240.0, 300.0), &mut Pcg32::seed_from_u64(1)));
+
+        let mut hits = 0;
+        for _ in 0..300 {
+            hits += step(&mut simulation)
+                .iter()
+                .filter(|event| matches!(event, SimEvent::MonsterHit { .. }))
+                .count();
+            if hits == 3 {
+                break;
+            }
+        }
+        assert_eq!(simulation.get_humans()[0].hp, 14.0);
+        let description = simulation.take_evidence().describe_arms("arms").unwrap();
+        assert!(description.contains("about 5 a hit"), "{}", description);
+    }
+
+    #[test]
+    fn dead_humans_leave_their_drops_behind() {
+        let drops = Resources { biomass: 3, bones: 1 };
+        let mut simulation = Simulation::new(
+            vec![Archetype { drops, ..archetype() }],
+            WaveScript { nights: Vec::new() },
+            1,
+        );
+        simulation.add_monster_at(monster(point(200.0, 250.0)));
+        simulation.add_human(human(point(240.0, 300.0)));
+        simulation.add_human(human(point(240.0, 300.0)));
+
+        for _ in 0..120 {
+            if simulation.get_humans().is_empty() {
+                break;
+            }
+            step(&mut simulation);
+        }
+        assert!(simulation.get_humans().is_empty());
+        assert_eq!(simulation.take_harvest(), drops + drops);
+        // and it's only handed over once
+        assert_eq!(simulation.take_harvest(), Resources::default());
+    }
+
+    #[test]
+    fn experience_ranks_monsters_up_and_toughens_them() {
+        let mut simulation = simulation();
+        let mut veteran = monster(point(200.0, 250.0));
+        veteran.hp = 60.0;
+        simulation.add_monster_at(veteran);
+
+        let mut events = Vec::new();
+        simulation.credit_monster(0, 0.0, true, &mut events);
+        assert!(events.is_empty());
+        assert_eq!(simulation.get_monsters()[0].get_level(), 1);
+
+        simulation.credit_monster(0, 0.0, true, &mut events);
+        let veteran = &simulation.get_monsters()[0];
+        assert_eq!(veteran.get_kills(), 2);
+        assert_eq!(veteran.get_level(), 2);
+        assert!(matches!(events[..], [SimEvent::MonsterRankedUp { monster: 0, level: 2, .. }]), "{:?}", events);
+        // the extra hp comes with the level, so it's still missing the same 40
+        let bonus = veterancy::LevelBonus::for_level(2).health;
+        assert!((veteran.get_total_hp() - 100.0 * bonus).abs() < 1e-4);
+        assert!((veteran.get_hp() - (100.0 * bonus - 40.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn experience_is_only_for_the_hp_a_human_had_left() {
+        let mut simulation = simulation();
+        simulation.add_monster_at(monster(point(200.0, 250.0)));
+        simulation.add_human(human(point(240.0, 300.0)));
+
+        // a 30 damage blow on a human with 20 hp
+        step(&mut simulation);
+        assert!(simulation.get_humans().is_empty());
+        let xp = 20.0 * veterancy::XP_PER_DAMAGE + veterancy::XP_PER_KILL;
+        assert!((simulation.get_monsters()[0].get_xp() - xp).abs() < 1e-4);
+    }
+
+    #[test]
+    fn seasoned_rosters_face_tougher_humans() {
+        let waves = WaveScript {
+            nights: vec![Night {
+                win: WinCondition::KillAll,
+                spawns: vec![Spawn {
+                    time: 0.0,
+                    at: SpawnPoint::Edge(Edge::Right),
+                    units: SpawnUnits::Archetype("test".to_string(), 1),
+                }],
+            }],
+        };
+        let mut simulation = Simulation::new(vec![archetype()], waves, 1);
+        for xp in &[50.0, 20.0, 0.0] {
+            let mut template = monster(point(100.0, 100.0));
+            template.xp = *xp;
+            simulation.add_monster_at(template);
+        }
+        simulation.start_night(1);
+        step(&mut simulation);
+
+        let factor = veterancy::get_difficulty_factor([3, 2, 1].iter().copied());
+        assert!(factor > 1.0);
+        let human = &simulation.get_humans()[0];
+        assert!((human.total_hp - 20.0 * factor).abs() < 1e-4);
+        assert!((human.attack.damage - 5.0 * factor).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bodies_recover_overnight_and_more_for_a_rest() {
+        let mut simulation = simulation();
+        let body = |recovery: f32| {
+            Body::new("body".to_string(), "Body".to_string(), 0, 100.0, 0.0, Resistances::default()).with_recovery(recovery)
+        };
+        for recovery in &[0.3, 0.3, 0.0] {
+            let mut template = monster(point(100.0, 100.0));
+            template.body = body(*recovery);
+            simulation.add_monster_at(template);
+        }
+        let hp = [40.0, 90.0, 40.0];
+        for (monster, hp) in simulation.monsters.iter_mut().zip(&hp) {
+            monster.hp = *hp;
+        }
+
+        simulation.recover_at_dawn();
+        let healed: Vec<_> = simulation.get_monsters().iter().map(Monster::get_hp).collect();
+        // a 30% body heals 30 of its 100 hp, but never past full, and a 0% body not at all
+        assert_eq!(healed, vec![70.0, 100.0, 40.0]);
+
+        simulation.rest_monsters();
+        let rested: Vec<_> = simulation.get_monsters().iter().map(Monster::get_hp).collect();
+        assert_eq!(rested, vec![100.0, 100.0, 40.0 + 100.0 * REST_RECOVERY]);
+    }
+}
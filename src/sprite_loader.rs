@@ -1,40 +1,439 @@
-use crate::monster_build::{Arms, Body, Head, Legs};
-use ggez::{graphics, Context};
+use crate::ability::{ArmsAbility, BodyAbility, HeadAbility, LegsAbility};
+use crate::combat::{Attack, DamageType, Resistances};
+use crate::economy::Resources;
+use crate::monster::Archetype;
+use crate::monster_build::{Arms, Body, Head, Legs, PartSprites, RangedAttack};
+use crate::status::{StatusEffect, StatusKind};
+use ggez::{filesystem, graphics, Context, GameError, GameResult};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::f32::consts::PI;
+use std::fmt::Display;
 
-pub fn get_heads(ctx: &mut Context) -> Vec<Head> {
-    let head1 = graphics::Image::new(ctx, "/sprites/googly-eyes.png").unwrap();
-    let head2 = graphics::Image::new(ctx, "/sprites/longeyes.png").unwrap();
-    let head3 = graphics::Image::new(ctx, "/sprites/thanos-head.png").unwrap();
+pub const PART_CATALOGUE_PATH: &str = "/parts.ron";
+pub const HUMAN_CATALOGUE_PATH: &str = "/humans.ron";
 
-    vec![Head::new(head1, 130.0), Head::new(head2, 180.0), Head::new(head3, 105.0)]
+/// Every part the builder can offer, with their images already loaded.
+#[derive(Clone)]
+pub struct PartCatalogue {
+    pub heads: Vec<Head>,
+    pub bodies: Vec<Body>,
+    pub arms: Vec<Arms>,
+    pub legs: Vec<Legs>,
+    pub sprites: PartSprites,
 }
 
-pub fn get_bodies(ctx: &mut Context) -> Vec<Body> {
-    let body1 = graphics::Image::new(ctx, "/sprites/round-body.png").unwrap();
-    let body2 = graphics::Image::new(ctx, "/sprites/muscle-body.png").unwrap();
-    let body3 = graphics::Image::new(ctx, "/sprites/gingerbread-body.png").unwrap();
+impl PartCatalogue {
+    pub fn find_head(&self, id: &str) -> Option<&Head> {
+        self.heads.iter().find(|head| head.get_id() == id)
+    }
 
-    vec![Body::new(body1, 100.0), Body::new(body2, 80.0), Body::new(body3, 60.0)]
+    pub fn find_body(&self, id: &str) -> Option<&Body> {
+        self.bodies.iter().find(|body| body.get_id() == id)
+    }
+
+    pub fn find_arms(&self, id: &str) -> Option<&Arms> {
+        self.arms.iter().find(|arms| arms.get_id() == id)
+    }
+
+    pub fn find_legs(&self, id: &str) -> Option<&Legs> {
+        self.legs.iter().find(|legs| legs.get_id() == id)
+    }
+}
+
+/// Every kind of human that can attack, with `sprites` lined up with `archetypes`.
+#[derive(Clone)]
+pub struct HumanCatalogue {
+    pub archetypes: Vec<Archetype>,
+    pub sprites: Vec<graphics::Image>,
+}
+
+#[derive(Deserialize)]
+struct HumanCatalogueFile {
+    archetypes: Vec<Archetype>,
+}
+
+#[derive(Deserialize)]
+struct CatalogueFile {
+    heads: Vec<HeadEntry>,
+    bodies: Vec<BodyEntry>,
+    arms: Vec<ArmsEntry>,
+    legs: Vec<LegsEntry>,
+}
+
+#[derive(Deserialize)]
+struct HeadEntry {
+    id: String,
+    name: String,
+    sprite: String,
+    sight_range: f32,
+    field_of_view: f32,
+    #[serde(default)]
+    abilities: Vec<HeadAbility>,
+    #[serde(default)]
+    cost: Resources,
+}
+
+#[derive(Deserialize)]
+struct BodyEntry {
+    id: String,
+    name: String,
+    sprite: String,
+    health: f32,
+    #[serde(default)]
+    regeneration: f32,
+    #[serde(default)]
+    recovery: f32,
+    #[serde(default)]
+    resistances: Resistances,
+    #[serde(default)]
+    abilities: Vec<BodyAbility>,
+    #[serde(default)]
+    cost: Resources,
+}
+
+#[derive(Deserialize)]
+struct ArmsEntry {
+    id: String,
+    name: String,
+    sprite: String,
+    damage: f32,
+    damage_type: DamageType,
+    #[serde(default)]
+    crit_chance: f32,
+    reach: f32,
+    #[serde(default)]
+    ranged: Option<RangedAttack>,
+    #[serde(default)]
+    on_hit: Option<StatusEffect>,
+    #[serde(default)]
+    abilities: Vec<ArmsAbility>,
+    #[serde(default)]
+    cost: Resources,
+}
+
+#[derive(Deserialize)]
+struct LegsEntry {
+    id: String,
+    name: String,
+    sprite: String,
+    speed: f32,
+    #[serde(default)]
+    evasion: f32,
+    #[serde(default)]
+    abilities: Vec<LegsAbility>,
+    #[serde(default)]
+    cost: Resources,
+}
+
+/// the fields every kind of part has, so they can be validated the same way
+trait CatalogueEntry {
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+    fn sprite(&self) -> &str;
+    fn stats(&self) -> Vec<(&'static str, f32)>;
+}
+
+macro_rules! impl_catalogue_entry {
+    ($entry:ty, $($stat:ident),+) => {
+        impl CatalogueEntry for $entry {
+            fn id(&self) -> &str {
+                &self.id
+            }
+
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn sprite(&self) -> &str {
+                &self.sprite
+            }
+
+            fn stats(&self) -> Vec<(&'static str, f32)> {
+                vec![$((stringify!($stat), self.$stat)),+]
+            }
+        }
+    };
 }
 
-pub fn get_arms(ctx: &mut Context) -> Vec<Arms> {
-    let arms1 = graphics::Image::new(ctx, "/sprites/small-arms.png").unwrap();
-    let arms2 = graphics::Image::new(ctx, "/sprites/muscle-arms.png").unwrap();
-    let arms3 = graphics::Image::new(ctx, "/sprites/sharp-arms.png").unwrap();
+impl_catalogue_entry!(HeadEntry, sight_range, field_of_view);
+impl_catalogue_entry!(BodyEntry, health);
+impl_catalogue_entry!(ArmsEntry, damage, reach);
+impl_catalogue_entry!(LegsEntry, speed);
+
+/// reads and validates the part catalogue, e.g. `PART_CATALOGUE_PATH`
+pub fn load_part_catalogue(ctx: &mut Context, path: &str) -> GameResult<PartCatalogue> {
+    let file = filesystem::open(ctx, path)?;
+    let catalogue: CatalogueFile =
+        ron::de::from_reader(file).map_err(|error| catalogue_error(path, error))?;
 
-    vec![Arms::new(arms1, 5.0), Arms::new(arms2, 15.0), Arms::new(arms3, 20.0)]
+    let mut sprites = PartSprites {
+        heads: Vec::new(),
+        bodies: Vec::new(),
+        arms: Vec::new(),
+        legs: Vec::new(),
+    };
+    for entry in &catalogue.heads {
+        if entry.field_of_view > 2.0 * PI {
+            return Err(catalogue_error(
+                path,
+                format!("head \"{}\" can't see more than all the way round", entry.id),
+            ));
+        }
+    }
+    let heads = load_parts(ctx, path, "head", catalogue.heads, &mut sprites.heads, |entry, index| {
+        Head::new(entry.id, entry.name, index, entry.sight_range, entry.field_of_view)
+            .with_abilities(entry.abilities)
+            .with_cost(entry.cost)
+    })?;
+    for entry in &catalogue.bodies {
+        if !entry.regeneration.is_finite() || entry.regeneration < 0.0 {
+            return Err(catalogue_error(path, format!("body \"{}\" can't have negative regeneration", entry.id)));
+        }
+        check_chance(path, &format!("body \"{}\"", entry.id), "recovery", entry.recovery)?;
+        check_resistances(path, &format!("body \"{}\"", entry.id), &entry.resistances)?;
+        for ability in &entry.abilities {
+            match *ability {
+                BodyAbility::Armour(armour) => check_ability_size(path, "body", &entry.id, "Armour", armour)?,
+            }
+        }
+    }
+    let bodies = load_parts(ctx, path, "body", catalogue.bodies, &mut sprites.bodies, |entry, index| {
+        Body::new(entry.id, entry.name, index, entry.health, entry.regeneration, entry.resistances)
+            .with_recovery(entry.recovery)
+            .with_abilities(entry.abilities)
+            .with_cost(entry.cost)
+    })?;
+    for entry in &catalogue.arms {
+        if let Some(ranged) = entry.ranged {
+            if !ranged.speed.is_finite() || ranged.speed <= 0.0 || !ranged.range.is_finite() || ranged.range <= 0.0 {
+                return Err(catalogue_error(
+                    path,
+                    format!("arms \"{}\" need a ranged speed and range above zero", entry.id),
+                ));
+            }
+        }
+        if let Some(effect) = entry.on_hit {
+            check_effect(path, &format!("arms \"{}\"", entry.id), effect)?;
+        }
+        check_chance(path, &format!("arms \"{}\"", entry.id), "crit_chance", entry.crit_chance)?;
+        for ability in &entry.abilities {
+            match *ability {
+                ArmsAbility::Cleave(radius) => check_ability_size(path, "arms", &entry.id, "Cleave", radius)?,
+                ArmsAbility::Knockback(distance) => check_ability_size(path, "arms", &entry.id, "Knockback", distance)?,
+            }
+        }
+    }
+    let arms = load_parts(ctx, path, "arms", catalogue.arms, &mut sprites.arms, |entry, index| {
+        let attack = Attack {
+            damage: entry.damage,
+            damage_type: entry.damage_type,
+            crit_chance: entry.crit_chance,
+        };
+        Arms::new(entry.id, entry.name, index, attack, entry.reach, entry.ranged, entry.on_hit)
+            .with_abilities(entry.abilities)
+            .with_cost(entry.cost)
+    })?;
+    for entry in &catalogue.legs {
+        check_chance(path, &format!("legs \"{}\"", entry.id), "evasion", entry.evasion)?;
+    }
+    let legs = load_parts(ctx, path, "legs", catalogue.legs, &mut sprites.legs, |entry, index| {
+        Legs::new(entry.id, entry.name, index, entry.speed, entry.evasion)
+            .with_abilities(entry.abilities)
+            .with_cost(entry.cost)
+    })?;
+
+    Ok(PartCatalogue {
+        heads,
+        bodies,
+        arms,
+        legs,
+        sprites,
+    })
 }
 
-pub fn get_legs(ctx: &mut Context) -> Vec<Legs> {
-    let legs1 = graphics::Image::new(ctx, "/sprites/blob-legs.png").unwrap();
-    let legs2 = graphics::Image::new(ctx, "/sprites/muscle-legs.png").unwrap();
+fn load_parts<E: CatalogueEntry, T>(
+    ctx: &mut Context,
+    path: &str,
+    kind: &str,
+    entries: Vec<E>,
+    sprites: &mut Vec<graphics::Image>,
+    build: impl Fn(E, usize) -> T,
+) -> GameResult<Vec<T>> {
+    if entries.is_empty() {
+        return Err(catalogue_error(path, format!("there must be at least one {} part", kind)));
+    }
+
+    let mut seen_ids = HashSet::new();
+    let mut parts = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let id = entry.id().to_string();
+        if id.is_empty() || entry.name().is_empty() {
+            return Err(catalogue_error(path, format!("every {} part needs an id and a name", kind)));
+        }
+        if !seen_ids.insert(id.clone()) {
+            return Err(catalogue_error(path, format!("{} id \"{}\" is used more than once", kind, id)));
+        }
+        for (stat, value) in entry.stats() {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(catalogue_error(
+                    path,
+                    format!("{} \"{}\" has {} {}, it must be above zero", kind, id, stat, value),
+                ));
+            }
+        }
+        if !filesystem::exists(ctx, entry.sprite()) {
+            return Err(catalogue_error(
+                path,
+                format!("{} \"{}\" uses the sprite {}, which does not exist", kind, id, entry.sprite()),
+            ));
+        }
+        let sprite = graphics::Image::new(ctx, entry.sprite()).map_err(|error| {
+            catalogue_error(path, format!("{} \"{}\" has a broken sprite: {}", kind, id, error))
+        })?;
 
-    vec![Legs::new(legs1, 5.0), Legs::new(legs2, 10.0)]
+        sprites.push(sprite);
+        parts.push(build(entry, sprites.len() - 1));
+    }
+    Ok(parts)
 }
 
-pub fn get_human_sprites(ctx: &mut Context) -> Vec<graphics::Image> {
-    let human1 = graphics::Image::new(ctx, "/sprites/gun-human.png").unwrap();
-    let human2 = graphics::Image::new(ctx, "/sprites/gun-human2.png").unwrap();
+fn check_ability_size(path: &str, kind: &str, id: &str, ability: &str, size: f32) -> GameResult {
+    if !size.is_finite() || size <= 0.0 {
+        return Err(catalogue_error(
+            path,
+            format!("{} \"{}\" has {} {}, it must be above zero", kind, id, ability, size),
+        ));
+    }
+    Ok(())
+}
+
+/// `owner` says whose chance it is, for the error
+fn check_chance(path: &str, owner: &str, chance_name: &str, chance: f32) -> GameResult {
+    if !(0.0..=1.0).contains(&chance) {
+        return Err(catalogue_error(
+            path,
+            format!("{} has {} {}, it must be from 0 to 1", owner, chance_name, chance),
+        ));
+    }
+    Ok(())
+}
+
+fn check_resistances(path: &str, owner: &str, resistances: &Resistances) -> GameResult {
+    for (damage_type, resistance) in resistances.get_all().iter() {
+        if !resistance.is_finite() || *resistance > 1.0 {
+            return Err(catalogue_error(
+                path,
+                format!("{} has a {} resistance of {}, it can be at most 1", owner, damage_type, resistance),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `owner` says what causes the effect, for the error
+fn check_effect(path: &str, owner: &str, effect: StatusEffect) -> GameResult {
+    // a stun is all or nothing, so only its duration matters
+    let strength_ok = effect.kind == StatusKind::Stun || (effect.strength.is_finite() && effect.strength > 0.0);
+    if !effect.duration.is_finite() || effect.duration <= 0.0 || !strength_ok {
+        return Err(catalogue_error(
+            path,
+            format!("{} cause {:?} with a strength and duration that must be above zero", owner, effect.kind),
+        ));
+    }
+    if effect.kind == StatusKind::Slow && effect.strength >= 1.0 {
+        return Err(catalogue_error(path, format!("{} cause a Slow that must leave less than all their speed", owner)));
+    }
+    Ok(())
+}
+
+fn catalogue_error(path: &str, error: impl Display) -> GameError {
+    GameError::ResourceLoadError(format!("{}: {}", path, error))
+}
+
+/// reads and validates the human archetypes, e.g. `HUMAN_CATALOGUE_PATH`
+pub fn load_human_catalogue(ctx: &mut Context, path: &str) -> GameResult<HumanCatalogue> {
+    let file = filesystem::open(ctx, path)?;
+    let catalogue: HumanCatalogueFile =
+        ron::de::from_reader(file).map_err(|error| catalogue_error(path, error))?;
+
+    if !catalogue
+        .archetypes
+        .iter()
+        .any(|archetype| archetype.first_day <= 1 && archetype.cost <= 1)
+    {
+        return Err(catalogue_error(path, "the first night needs an archetype costing 1"));
+    }
+
+    let mut seen_ids = HashSet::new();
+    let mut sprites = Vec::with_capacity(catalogue.archetypes.len());
+    for archetype in &catalogue.archetypes {
+        let id = &archetype.id;
+        if id.is_empty() || archetype.name.is_empty() {
+            return Err(catalogue_error(path, "every archetype needs an id and a name"));
+        }
+        if !seen_ids.insert(id.clone()) {
+            return Err(catalogue_error(path, format!("archetype id \"{}\" is used more than once", id)));
+        }
+        for (stat, (min, max)) in &[
+            ("speed", archetype.speed),
+            ("range", archetype.range),
+            ("hp", archetype.hp),
+            ("damage", archetype.damage),
+        ] {
+            if !min.is_finite() || !max.is_finite() || *min <= 0.0 || min > max {
+                return Err(catalogue_error(
+                    path,
+                    format!("archetype \"{}\" has a {} range of ({}, {}), it must be above zero and in order", id, stat, min, max),
+                ));
+            }
+        }
+        if !archetype.turn_rate.is_finite() || archetype.turn_rate <= 0.0 {
+            return Err(catalogue_error(path, format!("archetype \"{}\" must be able to turn", id)));
+        }
+        if !archetype.bullet_speed.is_finite() || archetype.bullet_speed <= 0.0 {
+            return Err(catalogue_error(path, format!("archetype \"{}\" needs a bullet speed above zero", id)));
+        }
+        if !archetype.spread.is_finite() || archetype.spread < 0.0 {
+            return Err(catalogue_error(path, format!("archetype \"{}\" can't have a negative spread", id)));
+        }
+        let owner = format!("archetype \"{}\"", id);
+        if let Some(effect) = archetype.on_hit {
+            check_effect(path, &owner, effect)?;
+        }
+        check_chance(path, &owner, "crit_chance", archetype.crit_chance)?;
+        check_chance(path, &owner, "evasion", archetype.evasion)?;
+        check_resistances(path, &owner, &archetype.resistances)?;
+        if !archetype.armour.is_finite() || archetype.armour < 0.0 {
+            return Err(catalogue_error(path, format!("archetype \"{}\" can't have negative armour", id)));
+        }
+        let retreat_below = archetype.tactics.retreat_below;
+        if !(0.0..1.0).contains(&retreat_below) {
+            return Err(catalogue_error(
+                path,
+                format!("archetype \"{}\" has retreat_below {}, it must be from 0 up to 1", id, retreat_below),
+            ));
+        }
+        if !archetype.reload.is_finite() || archetype.reload <= 0.0 || archetype.cost == 0 || archetype.weight == 0 {
+            return Err(catalogue_error(
+                path,
+                format!("archetype \"{}\" needs a reload, cost and weight above zero", id),
+            ));
+        }
+        if !filesystem::exists(ctx, &archetype.sprite) {
+            return Err(catalogue_error(
+                path,
+                format!("archetype \"{}\" uses the sprite {}, which does not exist", id, archetype.sprite),
+            ));
+        }
+        let sprite = graphics::Image::new(ctx, &archetype.sprite).map_err(|error| {
+            catalogue_error(path, format!("archetype \"{}\" has a broken sprite: {}", id, error))
+        })?;
+        sprites.push(sprite);
+    }
 
-    vec![human1, human2]
+    Ok(HumanCatalogue {
+        archetypes: catalogue.archetypes,
+        sprites,
+    })
 }
@@ -0,0 +1,277 @@
+//! Lingering effects on monsters and humans, like burning or being slowed, that wear
+//! off after a while. Parts and archetypes can cause them when they hit.
+
+use crate::combat::{DamageType, Resistances};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum StatusKind {
+    /// loses `strength` hp a second, as fire damage
+    Burning,
+    /// loses `strength` hp a second for each dose
+    Poison,
+    /// moves at `strength` of its usual speed, e.g. 0.5 for half
+    Slow,
+    /// can't move, turn or attack
+    Stun,
+    /// loses `strength` hp a second for each open wound, as slashing damage
+    Bleed,
+    /// gets back `strength` hp a second
+    Regeneration,
+}
+
+/// What happens when an effect is applied to a unit that already has one of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    /// only one at a time, keeping the stronger and longer of the two
+    Refresh,
+    /// several at once each wearing off on its own, up to this many, replacing
+    /// the one closest to wearing off when full
+    Stack(usize),
+}
+
+impl StatusKind {
+    pub fn get_stacking(self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Stack(5),
+            StatusKind::Bleed => Stacking::Stack(3),
+            StatusKind::Burning | StatusKind::Slow | StatusKind::Stun | StatusKind::Regeneration => {
+                Stacking::Refresh
+            }
+        }
+    }
+
+    /// hp lost a second for each `strength`, negative for healing
+    fn get_damage_rate(self) -> f32 {
+        match self {
+            StatusKind::Burning | StatusKind::Poison | StatusKind::Bleed => 1.0,
+            StatusKind::Regeneration => -1.0,
+            StatusKind::Slow | StatusKind::Stun => 0.0,
+        }
+    }
+
+    /// the resistance that takes the edge off the damage, if any does
+    fn get_damage_type(self) -> Option<DamageType> {
+        match self {
+            StatusKind::Burning => Some(DamageType::Fire),
+            StatusKind::Bleed => Some(DamageType::Slashing),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// what this means depends on the kind, see `StatusKind`
+    pub strength: f32,
+    /// seconds until it wears off
+    pub duration: f32,
+    /// the index of the monster that caused it, to be credited if it kills
+    #[serde(skip)]
+    pub source: Option<usize>,
+}
+
+/// Every effect on one unit.
+#[derive(Debug, Clone, Default)]
+pub struct Statuses {
+    effects: Vec<StatusEffect>,
+}
+
+impl Statuses {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let same_kind = |existing: &&mut StatusEffect| existing.kind == effect.kind;
+        match effect.kind.get_stacking() {
+            Stacking::Refresh => match self.effects.iter_mut().find(same_kind) {
+                Some(existing) => {
+                    existing.strength = match effect.kind {
+                        // a slow is stronger the less speed it leaves
+                        StatusKind::Slow => existing.strength.min(effect.strength),
+                        _ => existing.strength.max(effect.strength),
+                    };
+                    existing.duration = existing.duration.max(effect.duration);
+                    existing.source = effect.source;
+                }
+                None => self.effects.push(effect),
+            },
+            Stacking::Stack(limit) => {
+                if self.effects.iter().filter(|existing| existing.kind == effect.kind).count() < limit {
+                    self.effects.push(effect);
+                } else if let Some(shortest) = self
+                    .effects
+                    .iter_mut()
+                    .filter(|existing| existing.kind == effect.kind)
+                    .min_by(|a, b| a.duration.partial_cmp(&b.duration).unwrap())
+                {
+                    *shortest = effect;
+                }
+            }
+        }
+    }
+
+    /// wears every effect down by `seconds`, returning the hp lost over that time
+    /// after `resistances` (negative if healed)
+    pub fn tick(&mut self, seconds: f32, resistances: &Resistances) -> f32 {
+        let mut damage = 0.0;
+        for effect in &mut self.effects {
+            let resisted = effect.kind.get_damage_type().map_or(0.0, |damage_type| resistances.get(damage_type));
+            damage += effect.kind.get_damage_rate() * effect.strength * seconds.min(effect.duration) * (1.0 - resisted);
+            effect.duration -= seconds;
+        }
+        self.effects.retain(|effect| effect.duration > 0.0);
+        damage
+    }
+
+    /// the monster behind whichever effect is hurting the unit most, who gets the kill
+    /// if the unit dies of them
+    pub fn get_source(&self) -> Option<usize> {
+        self.effects
+            .iter()
+            .filter(|effect| effect.source.is_some() && effect.kind.get_damage_rate() > 0.0)
+            .max_by(|a, b| a.strength.partial_cmp(&b.strength).unwrap())
+            .and_then(|effect| effect.source)
+    }
+
+    /// keeps the effects pointing at the monsters that caused them once monster `index` is gone
+    pub fn remove_source(&mut self, index: usize) {
+        for effect in &mut self.effects {
+            effect.source = match effect.source {
+                Some(source) if source == index => None,
+                Some(source) if source > index => Some(source - 1),
+                source => source,
+            };
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.has(StatusKind::Stun)
+    }
+
+    /// how much of its usual speed the unit moves at
+    pub fn get_speed_factor(&self) -> f32 {
+        if self.is_stunned() {
+            return 0.0;
+        }
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == StatusKind::Slow)
+            .map(|effect| effect.strength)
+            .fold(1.0, f32::min)
+    }
+
+    pub fn get_effects(&self) -> &[StatusEffect] {
+        &self.effects
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn effect(kind: StatusKind, strength: f32, duration: f32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            strength,
+            duration,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn damage_wears_off_with_the_effect() {
+        let mut statuses = Statuses::default();
+        statuses.apply(effect(StatusKind::Burning, 4.0, 1.5));
+        assert!((statuses.tick(1.0, &Resistances::default()) - 4.0).abs() < EPSILON);
+        // only half a second was left to burn
+        assert!((statuses.tick(1.0, &Resistances::default()) - 2.0).abs() < EPSILON);
+        assert!(!statuses.has(StatusKind::Burning));
+        assert_eq!(statuses.tick(1.0, &Resistances::default()), 0.0);
+    }
+
+    #[test]
+    fn refreshing_keeps_the_strongest_and_longest() {
+        let mut statuses = Statuses::default();
+        statuses.apply(effect(StatusKind::Burning, 4.0, 1.0));
+        statuses.apply(effect(StatusKind::Burning, 2.0, 3.0));
+        assert_eq!(statuses.get_effects(), &[effect(StatusKind::Burning, 4.0, 3.0)]);
+
+        statuses.apply(effect(StatusKind::Slow, 0.5, 1.0));
+        statuses.apply(effect(StatusKind::Slow, 0.8, 2.0));
+        assert!((statuses.get_speed_factor() - 0.5).abs() < EPSILON);
+        assert_eq!(statuses.get_effects().len(), 2);
+    }
+
+    #[test]
+    fn stacks_add_up_to_their_limit() {
+        let mut statuses = Statuses::default();
+        for duration in 1..=5 {
+            statuses.apply(effect(StatusKind::Bleed, 1.0, duration as f32));
+        }
+        // three wounds at most, the newest replacing the closest to healing
+        assert_eq!(statuses.get_effects().len(), 3);
+        assert!((statuses.tick(0.5, &Resistances::default()) - 1.5).abs() < EPSILON);
+        let mut durations: Vec<_> = statuses.get_effects().iter().map(|effect| effect.duration).collect();
+        durations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(durations, vec![2.5, 3.5, 4.5]);
+    }
+
+    #[test]
+    fn regeneration_heals_and_stun_stops_movement() {
+        let mut statuses = Statuses::default();
+        statuses.apply(effect(StatusKind::Regeneration, 2.0, 10.0));
+        assert!((statuses.tick(1.0, &Resistances::default()) + 2.0).abs() < EPSILON);
+
+        assert_eq!(statuses.get_speed_factor(), 1.0);
+        statuses.apply(effect(StatusKind::Stun, 1.0, 0.5));
+        assert!(statuses.is_stunned());
+        assert_eq!(statuses.get_speed_factor(), 0.0);
+        statuses.tick(0.5, &Resistances::default());
+        assert!(!statuses.is_stunned());
+    }
+
+    #[test]
+    fn the_worst_effect_is_blamed() {
+        let mut statuses = Statuses::default();
+        statuses.apply(effect(StatusKind::Slow, 0.2, 5.0));
+        assert_eq!(statuses.get_source(), None);
+        statuses.apply(StatusEffect {
+            source: Some(3),
+            ..effect(StatusKind::Poison, 1.0, 5.0)
+        });
+        statuses.apply(StatusEffect {
+            source: Some(1),
+            ..effect(StatusKind::Bleed, 2.0, 5.0)
+        });
+        assert_eq!(statuses.get_source(), Some(1));
+
+        // monster 1 dies, taking its credit with it and moving monster 3 down
+        statuses.remove_source(1);
+        assert_eq!(statuses.get_source(), Some(2));
+    }
+
+    #[test]
+    fn resistances_soak_up_damage_over_time() {
+        let resistances = Resistances {
+            fire: 0.5,
+            slashing: 1.0,
+            ..Resistances::default()
+        };
+        let mut statuses = Statuses::default();
+        statuses.apply(effect(StatusKind::Burning, 4.0, 10.0));
+        assert!((statuses.tick(1.0, &resistances) - 2.0).abs() < EPSILON);
+
+        statuses.apply(effect(StatusKind::Bleed, 4.0, 10.0));
+        statuses.apply(effect(StatusKind::Poison, 1.0, 10.0));
+        assert!((statuses.tick(1.0, &resistances) - 3.0).abs() < EPSILON);
+    }
+}
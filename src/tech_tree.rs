@@ -0,0 +1,219 @@
+//! Which parts the builder holds back until the player has earned them. Progress counts
+//! across every run, like the journal, so a part once unlocked stays unlocked.
+
+use crate::economy::Resources;
+use crate::sprite_loader::PartCatalogue;
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+pub const TECH_TREE_PATH: &str = "/tech_tree.ron";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum UnlockCondition {
+    /// get to the start of this day in any run
+    Day(u16),
+    /// kill this many humans, all runs together
+    Kills(u32),
+    /// spend this much building monsters, all runs together
+    Spent(Resources),
+    /// build a monster with all of these parts, by id
+    Combination(Vec<String>),
+}
+
+impl UnlockCondition {
+    /// `get_name` turns a part id into what the player knows it as
+    pub fn describe(&self, progress: &Progress, get_name: &dyn Fn(&str) -> String) -> String {
+        match self {
+            UnlockCondition::Day(day) => format!("Reach day {} (best so far {})", day, progress.best_day),
+            UnlockCondition::Kills(kills) => format!("Kill {} humans ({} so far)", kills, progress.kills),
+            UnlockCondition::Spent(spent) => format!("Spend {} ({} so far)", spent, progress.spent),
+            UnlockCondition::Combination(parts) => {
+                let names: Vec<String> = parts.iter().map(|id| get_name(id)).collect();
+                format!("Build a monster with {}", names.join(" and "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Unlock {
+    /// the id of the part held back
+    pub part: String,
+    /// all of these have to be met
+    pub requires: Vec<UnlockCondition>,
+}
+
+/// Parts without an entry are there from the start.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TechTree {
+    pub unlocks: Vec<Unlock>,
+}
+
+impl TechTree {
+    pub fn get_requirements(&self, part_id: &str) -> &[UnlockCondition] {
+        self.unlocks
+            .iter()
+            .find(|unlock| unlock.part == part_id)
+            .map_or(&[], |unlock| &unlock.requires)
+    }
+
+    /// the conditions still standing between the player and a part, none if it's unlocked
+    pub fn get_unmet<'a>(&'a self, part_id: &str, progress: &Progress) -> Vec<&'a UnlockCondition> {
+        self.get_requirements(part_id)
+            .iter()
+            .filter(|condition| !progress.is_met(condition))
+            .collect()
+    }
+
+    pub fn is_unlocked(&self, part_id: &str, progress: &Progress) -> bool {
+        self.get_unmet(part_id, progress).is_empty()
+    }
+}
+
+/// How far the player has got, all runs together, kept in the journal.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    pub best_day: u16,
+    pub kills: u32,
+    pub spent: Resources,
+    /// the part ids of every monster built
+    pub builds: BTreeSet<BTreeSet<String>>,
+}
+
+impl Progress {
+    pub fn is_met(&self, condition: &UnlockCondition) -> bool {
+        match condition {
+            UnlockCondition::Day(day) => self.best_day >= *day,
+            UnlockCondition::Kills(kills) => self.kills >= *kills,
+            UnlockCondition::Spent(spent) => self.spent.can_afford(*spent),
+            UnlockCondition::Combination(parts) => self
+                .builds
+                .iter()
+                .any(|build| parts.iter().all(|part| build.contains(part))),
+        }
+    }
+
+    pub fn record_build(&mut self, part_ids: &[&str], cost: Resources) {
+        self.builds.insert(part_ids.iter().map(|id| id.to_string()).collect());
+        self.spent += cost;
+    }
+
+    pub fn merge(&mut self, other: &Progress) {
+        self.best_day = self.best_day.max(other.best_day);
+        self.kills += other.kills;
+        self.spent += other.spent;
+        self.builds.extend(other.builds.iter().cloned());
+    }
+}
+
+/// reads the tech tree, e.g. `TECH_TREE_PATH`, checking it only names parts in `catalogue`
+/// and leaves something to build every part of a monster from on day 1
+pub fn load_tech_tree(ctx: &mut Context, path: &str, catalogue: &PartCatalogue) -> GameResult<TechTree> {
+    let file = filesystem::open(ctx, path)?;
+    let tree: TechTree = ron::de::from_reader(file).map_err(|error| tree_error(path, error))?;
+
+    let is_part = |id: &str| {
+        catalogue.find_head(id).is_some()
+            || catalogue.find_body(id).is_some()
+            || catalogue.find_arms(id).is_some()
+            || catalogue.find_legs(id).is_some()
+    };
+    let mut seen = BTreeSet::new();
+    for unlock in &tree.unlocks {
+        if !is_part(&unlock.part) {
+            return Err(tree_error(path, format!("unknown part \"{}\"", unlock.part)));
+        }
+        if !seen.insert(unlock.part.as_str()) {
+            return Err(tree_error(path, format!("part \"{}\" is listed twice", unlock.part)));
+        }
+        for condition in &unlock.requires {
+            if let UnlockCondition::Combination(parts) = condition {
+                if parts.is_empty() {
+                    return Err(tree_error(path, format!("part \"{}\" needs an empty combination", unlock.part)));
+                }
+                for id in parts {
+                    if !is_part(id) {
+                        return Err(tree_error(path, format!("part \"{}\" needs unknown part \"{}\"", unlock.part, id)));
+                    }
+                    if id == &unlock.part {
+                        return Err(tree_error(path, format!("part \"{}\" needs itself to unlock", unlock.part)));
+                    }
+                }
+            }
+        }
+    }
+
+    let progress = Progress::default();
+    let unlocked = |ids: Vec<&str>| ids.into_iter().any(|id| tree.is_unlocked(id, &progress));
+    if !unlocked(catalogue.heads.iter().map(|head| head.get_id()).collect())
+        || !unlocked(catalogue.bodies.iter().map(|body| body.get_id()).collect())
+        || !unlocked(catalogue.arms.iter().map(|arms| arms.get_id()).collect())
+        || !unlocked(catalogue.legs.iter().map(|legs| legs.get_id()).collect())
+    {
+        return Err(tree_error(path, "every kind of part needs one that's unlocked from the start"));
+    }
+    Ok(tree)
+}
+
+fn tree_error(path: &str, error: impl std::fmt::Display) -> GameError {
+    GameError::ResourceLoadError(format!("{}: {}", path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> TechTree {
+        TechTree {
+            unlocks: vec![
+                Unlock {
+                    part: "sharp-arms".to_string(),
+                    requires: vec![UnlockCondition::Day(3), UnlockCondition::Kills(10)],
+                },
+                Unlock {
+                    part: "thanos-head".to_string(),
+                    requires: vec![UnlockCondition::Combination(vec![
+                        "long-eyes".to_string(),
+                        "muscle-body".to_string(),
+                    ])],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn parts_unlock_once_every_condition_is_met() {
+        let tree = tree();
+        let mut progress = Progress::default();
+        assert!(tree.is_unlocked("small-arms", &progress));
+        assert_eq!(tree.get_unmet("sharp-arms", &progress).len(), 2);
+
+        progress.best_day = 3;
+        assert_eq!(tree.get_unmet("sharp-arms", &progress), vec![&UnlockCondition::Kills(10)]);
+        progress.merge(&Progress {
+            kills: 10,
+            best_day: 1,
+            ..Progress::default()
+        });
+        assert_eq!(progress.best_day, 3);
+        assert!(tree.is_unlocked("sharp-arms", &progress));
+    }
+
+    #[test]
+    fn combinations_need_one_monster_with_every_part() {
+        let tree = tree();
+        let mut progress = Progress::default();
+        progress.record_build(&["long-eyes", "round-body", "small-arms", "blob-legs"], Resources::default());
+        progress.record_build(&["googly-eyes", "muscle-body", "small-arms", "blob-legs"], Resources::default());
+        assert!(!tree.is_unlocked("thanos-head", &progress));
+
+        progress.record_build(
+            &["long-eyes", "muscle-body", "small-arms", "blob-legs"],
+            Resources { biomass: 4, bones: 1 },
+        );
+        assert!(tree.is_unlocked("thanos-head", &progress));
+        assert_eq!(progress.spent, Resources { biomass: 4, bones: 1 });
+    }
+}
@@ -0,0 +1,73 @@
+//! Units and attacks for setting up nights in tests, shared so a new field only has to be
+//! filled in once.
+
+use crate::behaviour;
+use crate::combat::{Attack, DamageType, Resistances};
+use crate::economy::Resources;
+use crate::human_ai::Tactics;
+use crate::monster::{Archetype, Human, Monster};
+use crate::monster_build::{Arms, Body, Head, Legs};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::f32::consts::PI;
+
+pub fn point(x: f32, y: f32) -> mint::Point2<f32> {
+    mint::Point2 { x, y }
+}
+
+pub fn attack(damage: f32) -> Attack {
+    Attack {
+        damage,
+        damage_type: DamageType::Blunt,
+        crit_chance: 0.0,
+    }
+}
+
+/// humans that always roll the same `hp` and `damage`, with nothing special about them
+pub fn archetype(hp: f32, damage: f32, tactics: Tactics) -> Archetype {
+    Archetype {
+        id: "test".to_string(),
+        name: "Test".to_string(),
+        sprite: String::new(),
+        tint: (255, 255, 255),
+        speed: (100.0, 100.0),
+        range: (100.0, 100.0),
+        hp: (hp, hp),
+        damage: (damage, damage),
+        turn_rate: 100.0,
+        reload: 0.5,
+        bullet_speed: 600.0,
+        spread: 0.0,
+        cost: 1,
+        first_day: 1,
+        weight: 1,
+        tactics,
+        on_hit: None,
+        damage_type: DamageType::Ballistic,
+        crit_chance: 0.0,
+        armour: 0.0,
+        resistances: Resistances::default(),
+        evasion: 0.0,
+        drops: Resources::default(),
+    }
+}
+
+/// the first of the simulation's archetypes, which is always `archetype`
+pub fn human(archetype: &Archetype, pos: mint::Point2<f32>) -> Human {
+    Human::new(0, archetype, pos, &mut Pcg32::seed_from_u64(1))
+}
+
+/// 100 hp at most, and sees all the way round, so tests don't depend on which way it faces
+pub fn monster(hp: f32, damage: f32, pos: mint::Point2<f32>) -> Monster {
+    let mut monster = Monster::new(
+        "Test".to_string(),
+        Head::new("head".to_string(), "Head".to_string(), 0, 300.0, 2.0 * PI),
+        Body::new("body".to_string(), "Body".to_string(), 0, 100.0, 0.0, Resistances::default()),
+        Arms::new("arms".to_string(), "Arms".to_string(), 0, attack(damage), 40.0, None, None),
+        Legs::new("legs".to_string(), "Legs".to_string(), 0, 120.0, 0.0),
+        behaviour::default_behaviour(),
+        pos,
+    );
+    monster.hp = hp;
+    monster
+}
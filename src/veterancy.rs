@@ -0,0 +1,78 @@
+//! Monsters getting better at fighting the longer they live. Hits and kills earn them
+//! experience, each level makes them a little stronger than their parts alone, and the
+//! humans come back harder against a seasoned roster.
+
+/// experience for every point of damage dealt
+pub const XP_PER_DAMAGE: f32 = 0.2;
+pub const XP_PER_KILL: f32 = 10.0;
+
+/// the experience needed to reach each level, starting at level 1
+const LEVEL_XP: [f32; 5] = [0.0, 20.0, 50.0, 100.0, 175.0];
+const RANKS: [&str; 5] = ["Recruit", "Blooded", "Veteran", "Elite", "Legend"];
+pub const MAX_LEVEL: u32 = LEVEL_XP.len() as u32;
+
+// how much tougher the humans get for every level above the first across the roster, and the most
+const DIFFICULTY_PER_LEVEL: f32 = 0.04;
+const MAX_DIFFICULTY: f32 = 0.5;
+
+pub fn get_level(xp: f32) -> u32 {
+    LEVEL_XP.iter().filter(|&&needed| xp >= needed).count().max(1) as u32
+}
+
+pub fn get_rank(level: u32) -> &'static str {
+    RANKS[(level.clamp(1, MAX_LEVEL) - 1) as usize]
+}
+
+/// What a monster's level multiplies its parts' base stats by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelBonus {
+    pub health: f32,
+    pub damage: f32,
+    pub speed: f32,
+    pub sight: f32,
+}
+
+impl LevelBonus {
+    pub fn for_level(level: u32) -> Self {
+        let ranks = (level.clamp(1, MAX_LEVEL) - 1) as f32;
+        Self {
+            health: 1.0 + 0.1 * ranks,
+            damage: 1.0 + 0.1 * ranks,
+            speed: 1.0 + 0.04 * ranks,
+            sight: 1.0 + 0.05 * ranks,
+        }
+    }
+}
+
+/// what the humans' hp and damage are multiplied by against a roster at these levels
+pub fn get_difficulty_factor(levels: impl Iterator<Item = u32>) -> f32 {
+    let ranks: u32 = levels.map(|level| level.saturating_sub(1)).sum();
+    1.0 + (ranks as f32 * DIFFICULTY_PER_LEVEL).min(MAX_DIFFICULTY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn levels_follow_experience() {
+        assert_eq!(get_level(0.0), 1);
+        assert_eq!(get_level(19.9), 1);
+        assert_eq!(get_level(20.0), 2);
+        assert_eq!(get_level(1000.0), MAX_LEVEL);
+        assert_eq!(get_rank(get_level(60.0)), "Veteran");
+
+        let recruit = LevelBonus::for_level(1);
+        assert_eq!(recruit.health, 1.0);
+        assert!(LevelBonus::for_level(3).damage > recruit.damage);
+    }
+
+    #[test]
+    fn a_seasoned_roster_makes_for_harder_nights() {
+        assert_eq!(get_difficulty_factor([1, 1, 1].iter().copied()), 1.0);
+        assert!((get_difficulty_factor([3, 2, 1].iter().copied()) - 1.12).abs() < EPSILON);
+        assert!((get_difficulty_factor([MAX_LEVEL; 20].iter().copied()) - (1.0 + MAX_DIFFICULTY)).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,142 @@
+//! What a monster can see: humans within its sight range, inside the cone it is
+//! facing, with no tree in the way.
+
+use crate::movement;
+use ggez::graphics::Rect;
+
+/// Where a unit is looking from and how far round and how far out it can see.
+/// `field_of_view` is the full width of the cone in radians, centered on `heading`.
+#[derive(Debug, Clone, Copy)]
+pub struct ViewCone {
+    pub eye: mint::Point2<f32>,
+    pub heading: f32,
+    pub field_of_view: f32,
+    pub range: f32,
+}
+
+impl ViewCone {
+    /// whether `target` is in range, inside the cone and not hidden behind an obstacle
+    pub fn can_see(&self, target: mint::Point2<f32>, obstacles: &[Rect]) -> bool {
+        self.is_in_cone(target) && is_line_clear(self.eye, target, obstacles)
+    }
+
+    /// whether `target` is in range and inside the cone, ignoring obstacles
+    pub fn is_in_cone(&self, target: mint::Point2<f32>) -> bool {
+        let distance = movement::distance(self.eye, target);
+        if distance > self.range {
+            return false;
+        }
+        // nothing can sneak up by standing right on top of the eye
+        if distance == 0.0 {
+            return true;
+        }
+        let off_heading = movement::angle_difference(self.heading, movement::heading_towards(self.eye, target));
+        off_heading.abs() <= self.field_of_view / 2.0
+    }
+}
+
+/// whether the straight line from `from` to `to` misses every obstacle
+pub fn is_line_clear(from: mint::Point2<f32>, to: mint::Point2<f32>, obstacles: &[Rect]) -> bool {
+    !obstacles.iter().any(|obstacle| crosses(from, to, obstacle))
+}
+
+/// whether the segment from `from` to `to` passes through `rect`, clipping the
+/// segment against the rect one axis at a time
+fn crosses(from: mint::Point2<f32>, to: mint::Point2<f32>, rect: &Rect) -> bool {
+    let (mut enter, mut leave) = (0.0f32, 1.0f32);
+    let axes = [
+        (from.x, to.x - from.x, rect.x, rect.x + rect.w),
+        (from.y, to.y - from.y, rect.y, rect.y + rect.h),
+    ];
+    for &(start, delta, low, high) in &axes {
+        if delta == 0.0 {
+            if start < low || start > high {
+                return false;
+            }
+            continue;
+        }
+        let (mut t_low, mut t_high) = ((low - start) / delta, (high - start) / delta);
+        if t_low > t_high {
+            std::mem::swap(&mut t_low, &mut t_high);
+        }
+        enter = enter.max(t_low);
+        leave = leave.min(t_high);
+        if enter > leave {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn point(x: f32, y: f32) -> mint::Point2<f32> {
+        mint::Point2 { x, y }
+    }
+
+    fn cone(field_of_view: f32) -> ViewCone {
+        ViewCone {
+            eye: point(0.0, 0.0),
+            heading: 0.0,
+            field_of_view,
+            range: 100.0,
+        }
+    }
+
+    #[test]
+    fn sees_only_inside_the_cone() {
+        let narrow = cone(PI / 2.0);
+        assert!(narrow.is_in_cone(point(50.0, 0.0)));
+        assert!(narrow.is_in_cone(point(50.0, 40.0)));
+        assert!(!narrow.is_in_cone(point(20.0, 50.0)));
+        assert!(!narrow.is_in_cone(point(-50.0, 0.0)));
+
+        // a cone wider than half way round sees things off to the side
+        let wide = cone(3.0 * PI / 2.0);
+        assert!(wide.is_in_cone(point(20.0, 50.0)));
+        assert!(wide.is_in_cone(point(0.0, -50.0)));
+        assert!(!wide.is_in_cone(point(-50.0, 0.0)));
+    }
+
+    #[test]
+    fn sees_only_within_range() {
+        let cone = cone(PI);
+        assert!(cone.is_in_cone(point(100.0, 0.0)));
+        assert!(!cone.is_in_cone(point(101.0, 0.0)));
+        assert!(cone.is_in_cone(point(0.0, 0.0)));
+    }
+
+    #[test]
+    fn cones_facing_backwards_wrap_round_pi() {
+        let cone = ViewCone {
+            heading: PI,
+            ..cone(PI / 2.0)
+        };
+        assert!(cone.is_in_cone(point(-50.0, 10.0)));
+        assert!(cone.is_in_cone(point(-50.0, -10.0)));
+        assert!(!cone.is_in_cone(point(50.0, 0.0)));
+    }
+
+    #[test]
+    fn trees_block_the_view() {
+        let tree = [Rect::new(40.0, -10.0, 20.0, 20.0)];
+        let cone = cone(PI);
+        assert!(!cone.can_see(point(80.0, 0.0), &tree));
+        assert!(cone.can_see(point(30.0, 0.0), &tree));
+        assert!(cone.can_see(point(80.0, 40.0), &tree));
+    }
+
+    #[test]
+    fn lines_clip_against_each_side() {
+        let rect = Rect::new(10.0, 10.0, 10.0, 10.0);
+        assert!(crosses(point(0.0, 15.0), point(30.0, 15.0), &rect));
+        assert!(crosses(point(15.0, 0.0), point(15.0, 30.0), &rect));
+        assert!(crosses(point(0.0, 0.0), point(30.0, 30.0), &rect));
+        assert!(!crosses(point(0.0, 0.0), point(9.0, 9.0), &rect));
+        assert!(!crosses(point(0.0, 30.0), point(30.0, 25.0), &rect));
+        assert!(!crosses(point(0.0, 5.0), point(30.0, 5.0), &rect));
+    }
+}
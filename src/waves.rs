@@ -0,0 +1,109 @@
+use crate::monster::Archetype;
+use crate::SCREEN_SIZE;
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::Deserialize;
+
+pub const WAVE_SCRIPT_PATH: &str = "/waves.ron";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// somewhere random along an edge of the map, or an exact point
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum SpawnPoint {
+    Edge(Edge),
+    Point(f32, f32),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum SpawnUnits {
+    /// this many of one archetype, by id
+    Archetype(String, u32),
+    /// a weighted pick of whatever archetypes have turned up by this day, up to this cost
+    Budget(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum WinCondition {
+    /// last this many seconds, or kill everything the night sends
+    SurviveTimer(f32),
+    /// no timer, every scripted human has to die
+    KillAll,
+    /// last this many seconds with the nest standing, even if every monster falls
+    ProtectNest(f32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Spawn {
+    /// seconds into the night
+    pub time: f32,
+    pub at: SpawnPoint,
+    pub units: SpawnUnits,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Night {
+    pub win: WinCondition,
+    pub spawns: Vec<Spawn>,
+}
+
+/// One entry per night of the campaign, surviving the last one wins the game.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveScript {
+    pub nights: Vec<Night>,
+}
+
+/// reads the wave script, e.g. `WAVE_SCRIPT_PATH`, checking it only uses known archetypes
+pub fn load_wave_script(ctx: &mut Context, path: &str, archetypes: &[Archetype]) -> GameResult<WaveScript> {
+    let file = filesystem::open(ctx, path)?;
+    let script: WaveScript = ron::de::from_reader(file).map_err(|error| script_error(path, error))?;
+
+    if script.nights.is_empty() {
+        return Err(script_error(path, "there must be at least one night"));
+    }
+    for (i, night) in script.nights.iter().enumerate() {
+        let day = i + 1;
+        if night.spawns.is_empty() {
+            return Err(script_error(path, format!("night {} has no spawns", day)));
+        }
+        if let WinCondition::SurviveTimer(duration) | WinCondition::ProtectNest(duration) = night.win {
+            if !duration.is_finite() || duration <= 0.0 {
+                return Err(script_error(path, format!("night {} has no time to survive", day)));
+            }
+        }
+        for spawn in &night.spawns {
+            if !spawn.time.is_finite() || spawn.time < 0.0 {
+                return Err(script_error(path, format!("night {} has a spawn at {} seconds", day, spawn.time)));
+            }
+            if let SpawnPoint::Point(x, y) = spawn.at {
+                if !(0.0..=SCREEN_SIZE.0).contains(&x) || !(0.0..=SCREEN_SIZE.1).contains(&y) {
+                    return Err(script_error(path, format!("night {} spawns off the map at ({}, {})", day, x, y)));
+                }
+            }
+            match &spawn.units {
+                SpawnUnits::Archetype(id, count) => {
+                    if !archetypes.iter().any(|archetype| &archetype.id == id) {
+                        return Err(script_error(path, format!("night {} spawns unknown archetype \"{}\"", day, id)));
+                    }
+                    if *count == 0 {
+                        return Err(script_error(path, format!("night {} spawns zero \"{}\"", day, id)));
+                    }
+                }
+                SpawnUnits::Budget(0) => {
+                    return Err(script_error(path, format!("night {} spawns with an empty budget", day)));
+                }
+                SpawnUnits::Budget(_) => (),
+            }
+        }
+    }
+    Ok(script)
+}
+
+fn script_error(path: &str, error: impl std::fmt::Display) -> GameError {
+    GameError::ResourceLoadError(format!("{}: {}", path, error))
+}